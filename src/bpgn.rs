@@ -0,0 +1,727 @@
+use crate::bughouse_game::{BoardID, BughouseGame, ALL_SEATS};
+use crate::bughouse_move::BughouseMove;
+use crate::error::*;
+use chess::Color;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+// Keep movetext lines comfortably short
+pub(crate) const MAX_LINE_LEN: usize = 80;
+
+/// One move of a BPGN game, e.g. `12b. N@f2+{87.3}`
+#[derive(Clone, PartialEq, Debug)]
+pub struct BpgnMove {
+    board: BoardID,
+    color: Color,
+    number: u32,
+    mv: BughouseMove,
+    san: String,
+    clock: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl BpgnMove {
+    pub fn new(
+        board: BoardID,
+        color: Color,
+        number: u32,
+        mv: BughouseMove,
+        san: &str,
+    ) -> Self {
+        BpgnMove {
+            board,
+            color,
+            number,
+            mv,
+            san: san.to_string(),
+            clock: None,
+            comment: None,
+        }
+    }
+
+    #[inline]
+    pub fn get_board(&self) -> BoardID {
+        self.board
+    }
+
+    #[inline]
+    pub fn get_color(&self) -> Color {
+        self.color
+    }
+
+    /// The move number on this board (each board counts independently).
+    #[inline]
+    pub fn get_number(&self) -> u32 {
+        self.number
+    }
+
+    #[inline]
+    pub fn get_move(&self) -> &BughouseMove {
+        &self.mv
+    }
+
+    /// The move text as it appeared in the BPGN, e.g. `Nxf7+`
+    #[inline]
+    pub fn get_san(&self) -> &str {
+        &self.san
+    }
+
+    /// Time left on the mover's clock after the move.
+    #[inline]
+    pub fn get_clock(&self) -> Option<Duration> {
+        self.clock
+    }
+
+    #[inline]
+    pub fn get_comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn set_clock(&mut self, clock: Option<Duration>) {
+        self.clock = clock;
+    }
+
+    /// A comment ends at the first `}`, so any in `comment` are dropped.
+    pub fn set_comment(&mut self, comment: Option<String>) {
+        self.comment = comment.map(|comment| comment.replace('}', ""));
+    }
+
+    /// The move number tag, e.g. `12B` for White on board B, `12b` for Black.
+    pub fn tag(&self) -> String {
+        let board = self.board.to_string();
+        format!(
+            "{}{}",
+            self.number,
+            if self.color == Color::White {
+                board
+            } else {
+                board.to_lowercase()
+            }
+        )
+    }
+}
+
+/// A game read from or written to Bughouse PGN.
+///
+/// References:
+///   https://bughousedb.com/Lieven_BPGN_Standard.txt
+///
+/// ```
+/// use bughouse::{Bpgn, BoardID};
+/// use std::str::FromStr;
+///
+/// let bpgn = Bpgn::from_str(r#"[WhiteA "a"]
+///
+/// 1A. e4{179.9} 1B. d4 1a. d5 1b. Nf6 2A. exd5 2a. Qxd5 2B. P@e5 *"#).unwrap();
+/// assert_eq!(bpgn.get_header("WhiteA"), Some("a"));
+/// assert_eq!(bpgn.get_moves().len(), 7);
+/// let holdings = bpgn.get_game().get_board(BoardID::B).get_holdings();
+/// assert_eq!(holdings.to_string(), "p");
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bpgn {
+    headers: Vec<(String, String)>,
+    moves: Vec<BpgnMove>,
+    result: Option<String>,
+    initial: BughouseGame,
+    game: BughouseGame,
+}
+
+pub(crate) const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+impl Bpgn {
+    /// Start recording a game from `initial`.  Non-standard starting
+    /// positions are recorded in a `FEN` header.
+    pub fn new(initial: BughouseGame) -> Self {
+        let mut bpgn = Bpgn {
+            headers: Vec::new(),
+            moves: Vec::new(),
+            result: None,
+            game: initial.clone(),
+            initial,
+        };
+        for seat in ALL_SEATS.iter() {
+            if let Some(player) = bpgn.initial.get_player(*seat) {
+                let player = player.to_string();
+                bpgn.set_header(&seat.to_string(), &player);
+            }
+        }
+        let fen = bpgn.initial.to_bfen();
+        if fen != BughouseGame::default().to_bfen() {
+            bpgn.set_header("FEN", &fen);
+        }
+        bpgn
+    }
+
+    /// Set (or replace) a header
+    pub fn set_header(&mut self, key: &str, value: &str) {
+        set_header(&mut self.headers, key, value);
+    }
+
+    /// Set the game termination marker (`1-0`, `0-1`, `1/2-1/2` or `*`)
+    pub fn set_result(&mut self, result: &str) -> Result<(), Error> {
+        if !RESULTS.contains(&result) {
+            return Err(Error::BpgnParseError(format!(
+                "Invalid result: {}",
+                result
+            )));
+        }
+        self.result = Some(result.to_string());
+        Ok(())
+    }
+
+    /// Play and record `mv` on `board`, returning the recorded move so a
+    /// clock or comment can be attached.
+    pub fn push_move(
+        &mut self,
+        board: BoardID,
+        mv: &BughouseMove,
+    ) -> Result<&mut BpgnMove, Error> {
+        let bug_board = self.game.get_board(board);
+        let number = bug_board.get_fullmove_number();
+        let color = bug_board.side_to_move();
+        let san = mv.to_ban(bug_board);
+        self.game.make_move(board, mv)?;
+        self.moves
+            .push(BpgnMove::new(board, color, number, *mv, &san));
+        Ok(self.moves.last_mut().unwrap())
+    }
+
+    /// Write BPGN, optionally including `{clock}` comments.  Moves are
+    /// re-rendered as SAN from the replayed positions.
+    pub fn to_bpgn(&self, include_clocks: bool) -> String {
+        let mut res = String::new();
+        for (key, value) in &self.headers {
+            res.push_str(&format_header(key, value));
+        }
+        if !self.headers.is_empty() {
+            res.push('\n');
+        }
+
+        let mut game = self.initial.clone();
+        let mut line = String::new();
+        let mut push_token = |token: String, res: &mut String| {
+            if !line.is_empty() && line.len() + token.len() + 1 > MAX_LINE_LEN {
+                res.push_str(&line);
+                res.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        };
+        for bpgn_move in &self.moves {
+            let board = bpgn_move.get_board();
+            let san = bpgn_move.get_move().to_ban(game.get_board(board));
+            // Moves were validated when recorded
+            game.make_move(board, bpgn_move.get_move())
+                .expect("recorded moves are legal");
+            let mut token = format!("{}. {}", bpgn_move.tag(), san);
+            if let (true, Some(clock)) = (include_clocks, bpgn_move.clock) {
+                token.push_str(&format!("{{{:.1}}}", clock.as_secs_f64()));
+            }
+            push_token(token, &mut res);
+            if let Some(comment) = bpgn_move.get_comment() {
+                push_token(format!("{{{}}}", comment), &mut res);
+            }
+        }
+        push_token(
+            self.result.clone().unwrap_or_else(|| "*".to_string()),
+            &mut res,
+        );
+        res.push_str(&line);
+        res.push('\n');
+        res
+    }
+
+    /// All headers (tag pairs) in the order they appeared.
+    #[inline]
+    pub fn get_headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Look up a header, e.g. `WhiteA`, `BlackB`, `TimeControl` or `Result`
+    pub fn get_header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    #[inline]
+    pub fn get_moves(&self) -> &[BpgnMove] {
+        &self.moves
+    }
+
+    /// The game termination marker following the moves (`1-0`, `*`, etc)
+    #[inline]
+    pub fn get_result(&self) -> Option<&str> {
+        self.result.as_deref()
+    }
+
+    /// The starting position (from the `FEN` header, if any)
+    #[inline]
+    pub fn get_initial_game(&self) -> &BughouseGame {
+        &self.initial
+    }
+
+    /// The position after every move has been played.
+    #[inline]
+    pub fn get_game(&self) -> &BughouseGame {
+        &self.game
+    }
+}
+
+pub(crate) fn parse_header(line: &str) -> Result<(String, String), Error> {
+    let err = || Error::BpgnParseError(format!("Invalid header: {}", line));
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .ok_or_else(err)?;
+    let (key, value) = inner.split_once(' ').ok_or_else(err)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(err)?;
+    Ok((key.to_string(), unescape(value)))
+}
+
+// Undo `format_header`'s escaping: a backslash keeps the next character
+fn unescape(value: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => res.extend(chars.next()),
+            _ => res.push(c),
+        }
+    }
+    res
+}
+
+// Set (or replace) `key`, keeping the headers in order
+pub(crate) fn set_header(
+    headers: &mut Vec<(String, String)>,
+    key: &str,
+    value: &str,
+) {
+    match headers.iter_mut().find(|(k, _)| k == key) {
+        Some((_, v)) => *v = value.to_string(),
+        None => headers.push((key.to_string(), value.to_string())),
+    }
+}
+
+// A header line, e.g. `[Event "Casual game"]` and a newline
+pub(crate) fn format_header(key: &str, value: &str) -> String {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[{} \"{}\"]\n", key, value)
+}
+
+// Parse a move number tag like "12A." into (number, board, color)
+fn parse_move_number(token: &str) -> Option<(u32, BoardID, Color, &str)> {
+    let digits = token.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    let number = token[..digits].parse().ok()?;
+    let rest = &token[digits..];
+    let (board, color) = match rest.chars().next()? {
+        'A' => (BoardID::A, Color::White),
+        'a' => (BoardID::A, Color::Black),
+        'B' => (BoardID::B, Color::White),
+        'b' => (BoardID::B, Color::Black),
+        _ => return None,
+    };
+    let rest = rest[1..].strip_prefix('.')?;
+    Some((number, board, color, rest))
+}
+
+// Clock comments are either plain seconds ("{179.9}") or "[%clk 0:02:59.9]"
+fn parse_clock(comment: &str) -> Option<Duration> {
+    let comment = comment.trim();
+    let time = comment
+        .strip_prefix("[%clk")
+        .and_then(|c| c.strip_suffix(']'))
+        .unwrap_or(comment)
+        .trim();
+    let mut secs = 0.0;
+    for part in time.split(':') {
+        let val: f64 = part.parse().ok()?;
+        if !val.is_finite() || val < 0.0 {
+            return None;
+        }
+        secs = secs * 60.0 + val;
+    }
+    // Finite, but possibly too big for a Duration
+    if secs >= u64::MAX as f64 {
+        return None;
+    }
+    Some(Duration::from_secs_f64(secs))
+}
+
+impl FromStr for Bpgn {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut headers = Vec::new();
+        let mut movetext = String::new();
+        for line in input.lines() {
+            let trimmed = line.trim();
+            if movetext.trim().is_empty() && trimmed.starts_with('[') {
+                headers.push(parse_header(trimmed)?);
+            } else if !trimmed.starts_with('%') {
+                movetext.push_str(line);
+                movetext.push('\n');
+            }
+        }
+        let mut initial = match headers.iter().find(|(k, _)| k == "FEN") {
+            Some((_, fen)) => BughouseGame::from_str(fen)?,
+            None => BughouseGame::default(),
+        };
+        for (key, value) in &headers {
+            if let Some(seat) = ALL_SEATS.iter().find(|s| s.to_string() == *key)
+            {
+                initial.set_player(*seat, value);
+            }
+        }
+        let mut bpgn = Bpgn {
+            headers,
+            moves: Vec::new(),
+            result: None,
+            game: initial.clone(),
+            initial,
+        };
+        let mut pending: Option<(u32, BoardID, Color)> = None;
+        let mut rest = movetext.as_str();
+        loop {
+            rest = rest.trim_start();
+            let first = match rest.chars().next() {
+                Some(c) => c,
+                None => break,
+            };
+            if first == '{' || first == ';' {
+                let (comment, remaining) = if first == '{' {
+                    let end = rest.find('}').ok_or_else(|| {
+                        Error::BpgnParseError(format!(
+                            "Unterminated comment: {}",
+                            rest
+                        ))
+                    })?;
+                    (&rest[1..end], &rest[end + 1..])
+                } else {
+                    let end = rest.find('\n').unwrap_or(rest.len());
+                    (&rest[1..end], &rest[end..])
+                };
+                rest = remaining;
+                bpgn.add_comment(comment);
+                continue;
+            }
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '{' || c == ';')
+                .unwrap_or(rest.len());
+            let mut token = &rest[..end];
+            rest = &rest[end..];
+            if bpgn.result.is_some() {
+                return Err(Error::BpgnParseError(format!(
+                    "Unexpected text after result: {}",
+                    token
+                )));
+            }
+            if RESULTS.contains(&token) {
+                bpgn.result = Some(token.to_string());
+                continue;
+            }
+            if let Some((number, board, color, remaining)) =
+                parse_move_number(token)
+            {
+                pending = Some((number, board, color));
+                token = remaining;
+                if token.is_empty() {
+                    continue;
+                }
+            }
+            match pending.take() {
+                Some((number, board, color)) => {
+                    bpgn.play(number, board, color, token)?
+                }
+                None => {
+                    return Err(Error::BpgnParseError(format!(
+                        "Move without a move number: {}",
+                        token
+                    )))
+                }
+            }
+        }
+        Ok(bpgn)
+    }
+}
+
+impl Bpgn {
+    fn play(
+        &mut self,
+        number: u32,
+        board: BoardID,
+        color: Color,
+        san: &str,
+    ) -> Result<(), Error> {
+        let mut bpgn_move =
+            BpgnMove::new(board, color, number, BughouseMove::default(), san);
+        let fail = |err: Error| {
+            Error::BpgnMoveError(
+                format!("{}. {}", bpgn_move.tag(), san),
+                Box::new(err),
+            )
+        };
+        let bug_board = self.game.get_board(board);
+        if bug_board.side_to_move() != color {
+            return Err(fail(Error::IllegalMove(
+                san.to_string(),
+                IllegalMoveReason::WrongSideToMove,
+            )));
+        }
+        let mv = BughouseMove::from_ban(bug_board, san).map_err(fail)?;
+        self.game.make_move(board, &mv).map_err(fail)?;
+        bpgn_move.mv = mv;
+        self.moves.push(bpgn_move);
+        Ok(())
+    }
+
+    fn add_comment(&mut self, comment: &str) {
+        let last = match self.moves.last_mut() {
+            Some(last) => last,
+            // Ignore comments preceding the first move
+            None => return,
+        };
+        if last.clock.is_none() {
+            if let Some(clock) = parse_clock(comment) {
+                last.clock = Some(clock);
+                return;
+            }
+        }
+        let comment = comment.trim();
+        last.comment = Some(match last.comment.take() {
+            Some(prev) => format!("{} {}", prev, comment),
+            None => comment.to_string(),
+        });
+    }
+}
+
+impl fmt::Display for Bpgn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_bpgn(true))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bughouse_game::Seat;
+    use crate::bughouse_move::get_mv;
+    use crate::holdings::Holdings;
+
+    const GAME: &str = r#"[Event "Casual 2 0 bughouse"]
+[Site "bughousedb.com"]
+[Date "2021.05.30"]
+[WhiteA "alice"]
+[BlackA "bob"]
+[WhiteB "carol"]
+[BlackB "dave"]
+[TimeControl "120+0"]
+[Result "1-0"]
+
+1A. e4{119.8} 1a. e5{119.5} 1B. e4{119.7} 2A. Bc4{118.9}
+1b. d5{119.1} 2a. Nc6{118.2} 3A. Bxf7+{117.5} {A sacrifice}
+2B. exd5{118.8} 3a. Kxf7{117.1} 2b. Qxd5{118.0} 4A. Nf3{116.1}
+4a. Ke8{116.0} ; wise
+5A. Ng5{114.0} 5a. Nge7{115.1} 6A. P@f7#{113.2} 1-0
+"#;
+
+    #[test]
+    fn parse_game() {
+        let bpgn = Bpgn::from_str(GAME).unwrap();
+        assert_eq!(bpgn.get_headers().len(), 9);
+        assert_eq!(bpgn.get_header("WhiteA"), Some("alice"));
+        assert_eq!(bpgn.get_header("BlackB"), Some("dave"));
+        assert_eq!(bpgn.get_header("TimeControl"), Some("120+0"));
+        assert_eq!(bpgn.get_header("Result"), Some("1-0"));
+        assert_eq!(bpgn.get_header("Round"), None);
+        let black_b = Seat::new(BoardID::B, Color::Black);
+        assert_eq!(bpgn.get_game().get_player(black_b), Some("dave"));
+        assert_eq!(bpgn.get_result(), Some("1-0"));
+
+        let moves = bpgn.get_moves();
+        assert_eq!(moves.len(), 15);
+        assert_eq!(moves[4].tag(), "1b");
+        assert_eq!(moves[4].get_board(), BoardID::B);
+        assert_eq!(moves[4].get_color(), Color::Black);
+        assert_eq!(*moves[6].get_move(), get_mv("c4f7"));
+        assert_eq!(moves[6].get_san(), "Bxf7+");
+        assert_eq!(moves[6].get_clock(), Some(Duration::from_millis(117_500)));
+        assert_eq!(moves[6].get_comment(), Some("A sacrifice"));
+        assert_eq!(moves[11].get_comment(), Some("wise"));
+        assert_eq!(*moves[14].get_move(), get_mv("P@f7"));
+
+        let game = bpgn.get_game();
+        assert!(game.get_board(BoardID::A).is_mated());
+        assert_eq!(
+            *game.get_board(BoardID::A).get_holdings(),
+            Holdings::new(&[[0; 5], [1, 0, 0, 0, 0]])
+        );
+        assert_eq!(
+            bpgn.get_initial_game().to_bfen(),
+            BughouseGame::default().to_bfen()
+        );
+    }
+
+    #[test]
+    fn parse_fen_header() {
+        let bpgn = Bpgn::from_str(
+            r#"[FEN "4k3/8/8/8/8/8/8/4K3/Q w - - 0 1 | 4k3/8/8/8/8/8/8/4K3/ w - - 0 1"]
+
+1A. Q@e7+ {[%clk 0:01:59.5]} 1a. Kxe7 *"#,
+        )
+        .unwrap();
+        assert_eq!(bpgn.get_moves().len(), 2);
+        assert_eq!(
+            bpgn.get_moves()[0].get_clock(),
+            Some(Duration::from_millis(119_500))
+        );
+        assert_eq!(
+            bpgn.get_game()
+                .get_board(BoardID::B)
+                .get_holdings()
+                .to_string(),
+            "Q"
+        );
+        assert_eq!(bpgn.get_result(), Some("*"));
+    }
+
+    #[test]
+    fn report_failing_move() {
+        // White has no knight to drop
+        let err = Bpgn::from_str("1A. e4 1a. e5 2A. N@f6").unwrap_err();
+        match err {
+            Error::BpgnMoveError(mv, _) => assert_eq!(mv, "2A. N@f6"),
+            _ => panic!("Unexpected error: {}", err),
+        }
+
+        let err = Bpgn::from_str("1A. e4 1A. e5").unwrap_err();
+        match err {
+            Error::BpgnMoveError(mv, reason) => {
+                assert_eq!(mv, "1A. e5");
+                assert!(matches!(
+                    *reason,
+                    Error::IllegalMove(_, IllegalMoveReason::WrongSideToMove)
+                ));
+            }
+            _ => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn write_recorded_game() {
+        let mut game = BughouseGame::default();
+        game.set_player(Seat::new(BoardID::A, Color::White), "alice");
+        let mut bpgn = Bpgn::new(game);
+        assert_eq!(bpgn.get_header("WhiteA"), Some("alice"));
+        assert_eq!(bpgn.get_header("FEN"), None);
+        bpgn.set_header("BlackA", "bob");
+        bpgn.set_header("WhiteA", "al\"ice\"");
+        let moves = [
+            (BoardID::A, "e2e4"),
+            (BoardID::B, "d2d4"),
+            (BoardID::A, "d7d5"),
+            (BoardID::A, "e4d5"),
+            (BoardID::B, "P@e5"),
+            (BoardID::B, "e2e4"),
+            (BoardID::A, "d8d5"),
+            (BoardID::B, "e5d4"),
+        ];
+        for (i, (board, mv)) in moves.iter().enumerate() {
+            let recorded = bpgn.push_move(*board, &get_mv(mv)).unwrap();
+            recorded.set_clock(Some(Duration::from_millis(
+                120_000 - 1_000 * i as u64,
+            )));
+        }
+        bpgn.push_move(BoardID::A, &get_mv("b1c3"))
+            .unwrap()
+            .set_comment(Some("Gaining time".to_string()));
+        bpgn.set_result("*").unwrap();
+        assert!(bpgn.set_result("2-0").is_err());
+        assert_eq!(
+            bpgn.to_string(),
+            r#"[WhiteA "al\"ice\""]
+[BlackA "bob"]
+
+1A. e4{120.0} 1B. d4{119.0} 1a. d5{118.0} 2A. exd5{117.0} 1b. P@e5{116.0}
+2B. e4{115.0} 2a. Qxd5{114.0} 2b. exd4{113.0} 3A. Nc3 {Gaining time} *
+"#
+        );
+        assert_eq!(
+            bpgn.to_bpgn(false).lines().nth(3),
+            Some("1A. e4 1B. d4 1a. d5 2A. exd5 1b. P@e5 2B. e4 2a. Qxd5 2b. exd4 3A. Nc3")
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        let mut bpgn = Bpgn::new(BughouseGame::default());
+        bpgn.set_header("Site", r#"C:\games\"club".bpgn"#);
+        bpgn.push_move(BoardID::A, &get_mv("e2e4"))
+            .unwrap()
+            .set_comment(Some("{Best} by test".to_string()));
+        let written = bpgn.to_string();
+        assert!(written.starts_with(r#"[Site "C:\\games\\\"club\".bpgn"]"#));
+        assert!(written.contains("1A. e4 {{Best by test} *"));
+        let reread = Bpgn::from_str(&written).unwrap();
+        assert_eq!(reread.get_headers(), bpgn.get_headers());
+        assert_eq!(reread.get_moves()[0].get_comment(), Some("{Best by test"));
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let bpgn = Bpgn::from_str(GAME).unwrap();
+        let written = bpgn.to_string();
+        assert!(written.contains("3A. Bxf7+{117.5} {A sacrifice}"));
+        assert!(written.contains("6A. P@f7#{113.2} 1-0"));
+        let reread = Bpgn::from_str(&written).unwrap();
+        assert_eq!(reread.get_headers(), bpgn.get_headers());
+        assert_eq!(reread.get_game(), bpgn.get_game());
+        assert_eq!(reread.to_string(), written);
+        let moves = reread.get_moves().iter().zip(bpgn.get_moves());
+        for (a, b) in moves {
+            assert_eq!(a.get_move(), b.get_move());
+            assert_eq!(a.get_clock(), b.get_clock());
+            assert_eq!(a.tag(), b.tag());
+        }
+
+        // Non-standard starting positions keep their FEN
+        let bfen =
+            "4k3/8/8/8/8/8/8/4K3/Q w - - 0 1 | 4k3/8/8/8/8/8/8/4K3/ w - - 0 1";
+        let mut bpgn = Bpgn::new(BughouseGame::from_str(bfen).unwrap());
+        bpgn.push_move(BoardID::A, &get_mv("Q@e7")).unwrap();
+        let reread = Bpgn::from_str(&bpgn.to_string()).unwrap();
+        assert_eq!(reread.get_header("FEN"), Some(bfen));
+        assert_eq!(reread.get_game(), bpgn.get_game());
+    }
+
+    #[test]
+    fn reject_malformed() {
+        assert!(Bpgn::from_str("[WhiteA alice]\n\n1A. e4").is_err());
+        assert!(Bpgn::from_str("e4 e5").is_err());
+        assert!(Bpgn::from_str("1A. e4 {unterminated").is_err());
+        assert!(Bpgn::from_str("1A. e4 1-0 1a. e5").is_err());
+    }
+
+    #[test]
+    fn ignore_impossible_clocks() {
+        for clock in &["1e300", "18446744073709551615"] {
+            let input = format!("[Event \"x\"]\n\n1A. e4 {{{}}}", clock);
+            let bpgn = Bpgn::from_str(&input).unwrap();
+            assert_eq!(bpgn.get_moves()[0].get_clock(), None);
+        }
+    }
+}
@@ -1,14 +1,19 @@
 use crate::bughouse_move::BughouseMove;
+use crate::bughouse_movegen::BughouseMoveGen;
+use crate::chess960::{chess960_back_rank, CastlingNotation, CastlingRooks};
 use crate::error::*;
 use crate::holdings::*;
 use crate::promotions::Promotions;
+use crate::rules::Rules;
 use chess::{
-    between, get_rank, BitBoard, Board, BoardBuilder, BoardStatus, Color,  Piece, Rank,
-    Square, EMPTY,
+    between, get_bishop_moves, get_king_moves, get_knight_moves,
+    get_pawn_attacks, get_pawn_moves, get_rank, get_rook_moves, BitBoard,
+    Board, BoardBuilder, BoardStatus, Color, File, Piece, Rank, Square,
+    ALL_COLORS, ALL_FILES, ALL_PIECES, ALL_RANKS, EMPTY,
 };
 use std::convert::TryFrom;
 use std::str::FromStr;
-// use std::fmt;
+use std::fmt;
 
 /// A representation of one Bughouse board.
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -16,6 +21,12 @@ pub struct BughouseBoard {
     board: Board,
     holdings: Holdings,
     promos: Promotions,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    rules: Rules,
+    // Chess960 castling rights, when chess::Board's classical ones can't
+    // represent them (and chess::Board then has none)
+    castling_960: Option<CastlingRooks>,
 }
 
 impl BughouseBoard {
@@ -24,9 +35,40 @@ impl BughouseBoard {
             board,
             holdings,
             promos,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            rules: Rules::default(),
+            castling_960: None,
         }
     }
 
+    /// Chess960 starting position `id` (see `chess960_back_rank`), with
+    /// full castling rights.  `None` if `id` is out of range.
+    ///
+    /// ```
+    /// use bughouse::{BughouseBoard, CLASSICAL_CHESS960_ID};
+    ///
+    /// let board = BughouseBoard::chess960(0).unwrap();
+    /// assert_eq!(
+    ///     board.to_bfen(),
+    ///     "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR/ w KQkq - 0 1"
+    /// );
+    /// let classical = BughouseBoard::chess960(CLASSICAL_CHESS960_ID);
+    /// assert_eq!(classical, Some(BughouseBoard::default()));
+    /// ```
+    pub fn chess960(id: u16) -> Option<Self> {
+        let back_rank: String = chess960_back_rank(id)?
+            .iter()
+            .map(|piece| piece.to_string(Color::Black))
+            .collect();
+        let bfen = format!(
+            "{}/pppppppp/8/8/8/8/PPPPPPPP/{}/ w KQkq - 0 1",
+            back_rank,
+            back_rank.to_uppercase()
+        );
+        BughouseBoard::from_str(&bfen).ok()
+    }
+
     /// Get the source square (square the piece is currently on).
     #[inline]
     pub fn get_holdings(&self) -> &Holdings {
@@ -46,6 +88,48 @@ impl BughouseBoard {
     pub fn get_promos(&self) -> &Promotions {
         &self.promos
     }
+
+    /// Zobrist hash of the whole position: the `chess::Board` hash combined
+    /// with the `Holdings` counts and `Promotions` squares.  Each part is
+    /// updated incrementally as moves are made.  Move counters are ignored,
+    /// so repeated positions hash the same.
+    #[inline]
+    pub fn get_hash(&self) -> u64 {
+        self.board.get_hash()
+            ^ self.holdings.get_hash()
+            ^ self.promos.get_hash()
+            ^ self.castling_960.map_or(0, |rooks| rooks.get_hash())
+    }
+
+    /// Plies since the last capture or pawn move (or pawn drop).
+    #[inline]
+    pub fn get_halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Starts at 1 and is incremented after each Black move.
+    #[inline]
+    pub fn get_fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    #[inline]
+    pub fn get_rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Play by `rules` from now on.  (Moves already made aren't revisited.)
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+    }
+
+    /// The Chess960 castling rights, if the position has any that classical
+    /// castling (through `chess::Board`) can't express.  Castling is then
+    /// written as the king moving onto its own rook, e.g. `b1a1`.
+    #[inline]
+    pub fn get_castling_rooks(&self) -> Option<&CastlingRooks> {
+        self.castling_960.as_ref()
+    }
 }
 
 /// Construct the initial position.
@@ -56,13 +140,63 @@ impl Default for BughouseBoard {
             holdings: Holdings::default(),
             board: Board::default(),
             promos: Promotions::default(),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            rules: Rules::default(),
+            castling_960: None,
         }
     }
 }
 
-lazy_static! {
-    static ref BAD_PAWN_RANKS: BitBoard =
-        get_rank(Rank::Eighth) | get_rank(Rank::First);
+/// What `BughouseBoard::unmake_move` needs to take back a move: the position
+/// before it, short of the holdings, which only the dropped piece changed.
+/// Small and `Copy`, for searches that make and unmake moves rather than
+/// clone boards.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BoardUndo {
+    board: Board,
+    dropped: Option<Piece>,
+    captured: Option<(Piece, Square)>,
+    promos: Promotions,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    castling_960: Option<CastlingRooks>,
+}
+
+impl BoardUndo {
+    /// The piece the move dropped, if it was a drop.
+    #[inline]
+    pub fn get_dropped(&self) -> Option<Piece> {
+        self.dropped
+    }
+
+    /// The piece the move captured and the square it was on.
+    #[inline]
+    pub fn get_captured(&self) -> Option<(Piece, Square)> {
+        self.captured
+    }
+}
+
+/// Where the side to move stands, in bughouse terms.  A checkmate that a
+/// dropped piece could interpose isn't over yet: the player can block with a
+/// piece in hand, or sit and wait for their partner to send one.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub enum MateStatus {
+    /// There's a legal move or drop.
+    Ongoing,
+    /// In checkmate, were it not for a held piece that can be dropped to
+    /// block.
+    Blockable,
+    /// In checkmate unless the partner sends one of these piece types to
+    /// block with.  Pawns only count if they could be dropped to block (see
+    /// `Rules::get_pawn_drop_squares`).
+    MatedUnless(Vec<Piece>),
+    /// In checkmate, blockable or not: a double check, a knight check, or a
+    /// check from an adjacent square.
+    Mated,
+    /// Not in check, but without a legal move or drop.  The player must wait
+    /// for their partner to send something.
+    MustWait,
 }
 
 impl BughouseBoard {
@@ -74,16 +208,81 @@ impl BughouseBoard {
         self.board.king_square(self.board.side_to_move())
     }
 
+    /// Whether the side to move is checkmated with no chance of a drop to
+    /// block it (see `MateStatus::Mated`).
     pub fn is_mated(&self) -> bool {
-        // Disregard checkmates that have an an interposition
-        let checkers = self.board.checkers();
-        if self.board.status() != BoardStatus::Checkmate {
-            return false;
+        self.mate_status() == MateStatus::Mated
+    }
+
+    /// Classify the position for the side to move, accounting for drops.
+    ///
+    /// ```
+    /// use bughouse::{BughouseBoard, MateStatus};
+    /// use chess::Piece;
+    /// use std::str::FromStr;
+    ///
+    /// // Back rank mate, but a piece dropped on b1-g1 would block it
+    /// let bfen = "k7/8/8/8/8/8/PPP5/K6r w - - 0 1";
+    /// let board = BughouseBoard::from_str(bfen).unwrap();
+    /// assert_eq!(
+    ///     board.mate_status(),
+    ///     MateStatus::MatedUnless(vec![
+    ///         Piece::Knight,
+    ///         Piece::Bishop,
+    ///         Piece::Rook,
+    ///         Piece::Queen,
+    ///     ])
+    /// );
+    /// ```
+    pub fn mate_status(&self) -> MateStatus {
+        let color = self.board.side_to_move();
+        let drop_squares = self.drop_squares();
+        match self.board.status() {
+            BoardStatus::Ongoing => MateStatus::Ongoing,
+            // Only drops are left, and the move generator knows the rules
+            BoardStatus::Stalemate => {
+                if BughouseMoveGen::new_legal(self).len() > 0 {
+                    MateStatus::Ongoing
+                } else {
+                    MateStatus::MustWait
+                }
+            }
+            BoardStatus::Checkmate => {
+                // No squares to drop on when no interposition is possible
+                if drop_squares == EMPTY {
+                    return MateStatus::Mated;
+                }
+                let pawn_squares = self.rules.get_pawn_drop_squares(color);
+                let blockers: Vec<Piece> = ALL_PIECES[..NUM_HELD_PIECE_TYPES]
+                    .iter()
+                    .copied()
+                    .filter(|piece| {
+                        *piece != Piece::Pawn
+                            || drop_squares & pawn_squares != EMPTY
+                    })
+                    .collect();
+                if blockers.iter().any(|p| self.holdings.has_piece(color, *p)) {
+                    MateStatus::Blockable
+                } else {
+                    MateStatus::MatedUnless(blockers)
+                }
+            }
         }
-        let sq = checkers.to_square();
-        return checkers.popcnt() > 1
-            || self.board.piece_on(sq).unwrap() == Piece::Knight
-            || between(sq, self.king_square()) == EMPTY;
+    }
+
+    /// `by`'s pieces attacking `sq`.
+    pub(crate) fn attackers(&self, sq: Square, by: Color) -> BitBoard {
+        let board = &self.board;
+        let combined = *board.combined();
+        let diagonal = board.pieces(Piece::Bishop) | board.pieces(Piece::Queen);
+        let straight = board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+        let pawns = *board.pieces(Piece::Pawn);
+        let attackers = get_pawn_attacks(sq, !by, pawns)
+            | (get_knight_moves(sq) & board.pieces(Piece::Knight))
+            | (get_bishop_moves(sq, combined) & diagonal)
+            | (get_rook_moves(sq, combined) & straight)
+            | (get_king_moves(sq) & board.pieces(Piece::King));
+        attackers & board.color_combined(by)
     }
 
     fn blocks_check(&self, drop_sq: BitBoard) -> bool {
@@ -93,33 +292,284 @@ impl BughouseBoard {
             return false;
         }
         let checker_sq = checkers.to_square();
-        return self.board.piece_on(checker_sq).unwrap() != Piece::Knight
-            && (between(checker_sq, self.king_square()) & drop_sq != EMPTY);
-    }
-
-    pub fn make_move(&mut self, mv: &BughouseMove) -> Result<(), Error> {
-        if self.is_legal(mv) {
-            if mv.get_source() == None {
-                let piece = mv.get_piece().unwrap();
-                let color = self.board.side_to_move();
-                let mut builder = BoardBuilder::from(&self.board);
-                builder[mv.get_dest()] = Some((piece, color));
-                builder.en_passant(None);
-                builder.side_to_move(!self.board.side_to_move());
-                if let Ok(board) = Board::try_from(builder) {
-                    self.holdings.drop(color, piece)?;
-                    self.board = board;
-                    return Ok(());
+        self.board.piece_on(checker_sq).unwrap() != Piece::Knight
+            && (between(checker_sq, self.king_square()) & drop_sq != EMPTY)
+    }
+
+    /// All squares a held piece could legally be dropped on, ignoring the
+    /// pawn rank restriction and any ban on drop mates (see `Rules`).
+    pub(crate) fn drop_squares(&self) -> BitBoard {
+        let empty = !*self.board.combined();
+        let checkers = self.board.checkers();
+        if *checkers == EMPTY {
+            return empty;
+        }
+        if checkers.popcnt() != 1 {
+            return EMPTY;
+        }
+        // Only an interposition resolves check. Knights and adjacent checkers
+        // have no squares in between.
+        empty & between(checkers.to_square(), self.king_square())
+    }
+
+    /// Play `mv`, returning what `unmake_move` needs to take it back.
+    pub fn make_move(&mut self, mv: &BughouseMove) -> Result<BoardUndo, Error> {
+        self.check_move(mv)
+            .map_err(|reason| Error::IllegalMove(mv.to_string(), reason))?;
+        let color = self.board.side_to_move();
+        let undo = BoardUndo {
+            board: self.board,
+            dropped: mv.get_source().map_or(mv.get_piece(), |_| None),
+            captured: self.get_capture(mv),
+            promos: self.promos,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            castling_960: self.castling_960,
+        };
+        if mv.get_source().is_none() {
+            let piece = mv.get_piece().unwrap();
+            let board = self.board_after_drop(piece, mv.get_dest())?;
+            self.holdings.drop(color, piece)?;
+            self.board = board;
+            self.update_counters(color, piece == Piece::Pawn);
+        } else if let Some((king, rook)) = self.castle_960(mv) {
+            self.board = self.board_after_castle(king, rook)?;
+            if let Some(rooks) = self.castling_960.as_mut() {
+                rooks.clear_color(color);
+            }
+            self.update_counters(color, false);
+        } else {
+            let chess_mv = mv.to_chess_move().unwrap();
+            let src = chess_mv.get_source();
+            let moved = self.board.piece_on(src);
+            let is_zeroing = self.board.piece_on(mv.get_dest()).is_some()
+                || moved == Some(Piece::Pawn);
+            self.promos.record_move(color, chess_mv);
+            self.board = self.board.make_move_new(chess_mv);
+            self.update_counters(color, is_zeroing);
+            if let Some(rooks) = self.castling_960.as_mut() {
+                if moved == Some(Piece::King) {
+                    rooks.clear_color(color);
                 }
-                return Err(Error::IllegalMove(mv.to_string()));
-            } else {
-                let chess_mv = mv.to_chess_move().unwrap();
-                self.promos.record_move(self.board.side_to_move(), chess_mv);
-                self.board = self.board.make_move_new(chess_mv);
+                rooks.clear_square(src);
+                rooks.clear_square(mv.get_dest());
             }
-            return Ok(());
         }
-        return Err(Error::IllegalMove(mv.to_string()));
+        self.normalize_castling()?;
+        Ok(undo)
+    }
+
+    /// Take back the move `make_move` returned `undo` for, which must be the
+    /// last one made on this board.  A dropped piece goes back in hand, but
+    /// a captured piece is the caller's to take back from wherever they
+    /// sent it.  Fails, leaving the board as it is, if the dropped piece no
+    /// longer fits in hand (see `Holdings::add`).
+    ///
+    /// ```
+    /// use bughouse::{BughouseBoard, BughouseMove};
+    /// use std::str::FromStr;
+    ///
+    /// let start = "4k3/8/8/8/8/8/8/4K3/N w - - 0 1";
+    /// let mut board = BughouseBoard::from_str(start).unwrap();
+    /// let undo = board.make_move(&BughouseMove::from_str("N@e4").unwrap());
+    /// board.unmake_move(undo.unwrap()).unwrap();
+    /// assert_eq!(board.to_bfen(), start);
+    /// ```
+    pub fn unmake_move(&mut self, undo: BoardUndo) -> Result<(), Error> {
+        if let Some(piece) = undo.dropped {
+            self.holdings.add(undo.board.side_to_move(), piece)?;
+        }
+        self.board = undo.board;
+        self.promos = undo.promos;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+        self.castling_960 = undo.castling_960;
+        Ok(())
+    }
+
+    // Hand Chess960 castling rights over to chess::Board once it can
+    // represent them, so each position has one representation (and hash)
+    fn normalize_castling(&mut self) -> Result<(), Error> {
+        let rooks = match self.castling_960 {
+            Some(rooks) => rooks,
+            None => return Ok(()),
+        };
+        let mut builder = BoardBuilder::from(&self.board);
+        for color in ALL_COLORS.iter() {
+            let king = self.board.king_square(*color);
+            match rooks.classical_rights(*color, king) {
+                Some(rights) => builder.castle_rights(*color, rights),
+                None => return Ok(()),
+            };
+        }
+        self.board = Board::try_from(builder)?;
+        self.castling_960 = None;
+        Ok(())
+    }
+
+    /// The king's and rook's squares if `mv` is a Chess960 castle: the king
+    /// moving onto a rook it may castle with.
+    pub(crate) fn castle_960(
+        &self,
+        mv: &BughouseMove,
+    ) -> Option<(Square, Square)> {
+        let rooks = self.castling_960.as_ref()?;
+        let color = self.board.side_to_move();
+        let king = mv.get_source()?;
+        let rook = mv.get_dest();
+        let rank = color.to_my_backrank();
+        if king != self.board.king_square(color)
+            || king.get_rank() != rank
+            || rook.get_rank() != rank
+            || mv.get_piece().is_some()
+        {
+            return None;
+        }
+        let kingside = rook.get_file() > king.get_file();
+        if rooks.get(color, kingside) == Some(rook.get_file()) {
+            Some((king, rook))
+        } else {
+            None
+        }
+    }
+
+    /// The Chess960 castles available to the side to move.
+    pub(crate) fn castles_960(&self) -> Vec<BughouseMove> {
+        let rooks = match self.castling_960.as_ref() {
+            Some(rooks) => rooks,
+            None => return Vec::new(),
+        };
+        let color = self.board.side_to_move();
+        let king = self.board.king_square(color);
+        [true, false]
+            .iter()
+            .filter_map(|kingside| rooks.get(color, *kingside))
+            .map(|file| {
+                let rook = Square::make_square(color.to_my_backrank(), file);
+                BughouseMove::new(Some(king), rook, None)
+            })
+            .filter(|mv| self.is_legal(mv))
+            .collect()
+    }
+
+    // Where the king and rook end up: the g- and f-files castling kingside,
+    // or the c- and d-files queenside
+    fn castle_dests(king: Square, rook: Square) -> (Square, Square) {
+        let rank = king.get_rank();
+        if rook.get_file() > king.get_file() {
+            (
+                Square::make_square(rank, File::G),
+                Square::make_square(rank, File::F),
+            )
+        } else {
+            (
+                Square::make_square(rank, File::C),
+                Square::make_square(rank, File::D),
+            )
+        }
+    }
+
+    // Every square from `a` to `b` on a line, inclusive
+    fn span(a: Square, b: Square) -> BitBoard {
+        between(a, b) | BitBoard::from_square(a) | BitBoard::from_square(b)
+    }
+
+    // Chess960 castling: all squares the king and rook cross (or land on)
+    // are empty besides the two of them, and none of the king's are
+    // attacked, before or after
+    fn check_castle(
+        &self,
+        king: Square,
+        rook: Square,
+    ) -> Result<(), IllegalMoveReason> {
+        let (king_dest, rook_dest) = Self::castle_dests(king, rook);
+        let color = self.board.side_to_move();
+        let others = *self.board.combined()
+            ^ BitBoard::from_square(king)
+            ^ BitBoard::from_square(rook);
+        let king_path = Self::span(king, king_dest);
+        if (king_path | Self::span(rook, rook_dest)) & others != EMPTY {
+            return Err(IllegalMoveReason::InvalidPieceMove);
+        }
+        if king_path
+            .into_iter()
+            .any(|sq| self.attackers(sq, !color) != EMPTY)
+        {
+            return Err(IllegalMoveReason::LeavesKingInCheck);
+        }
+        self.board_after_castle(king, rook)
+            .map(|_| ())
+            .map_err(|_| IllegalMoveReason::LeavesKingInCheck)
+    }
+
+    // The chess::Board after the side to move castles (Chess960 style)
+    fn board_after_castle(
+        &self,
+        king: Square,
+        rook: Square,
+    ) -> Result<Board, Error> {
+        let (king_dest, rook_dest) = Self::castle_dests(king, rook);
+        let color = self.board.side_to_move();
+        let mut builder = BoardBuilder::from(&self.board);
+        builder[king] = None;
+        builder[rook] = None;
+        builder[king_dest] = Some((Piece::King, color));
+        builder[rook_dest] = Some((Piece::Rook, color));
+        builder.en_passant(None);
+        builder.side_to_move(!color);
+        Ok(Board::try_from(builder)?)
+    }
+
+    // The chess::Board after the side to move drops `piece` on `dest`
+    fn board_after_drop(
+        &self,
+        piece: Piece,
+        dest: Square,
+    ) -> Result<Board, Error> {
+        let mut builder = BoardBuilder::from(&self.board);
+        builder[dest] = Some((piece, self.board.side_to_move()));
+        builder.en_passant(None);
+        builder.side_to_move(!self.board.side_to_move());
+        Ok(Board::try_from(builder)?)
+    }
+
+    /// Whether dropping `piece` on (empty) `dest` would checkmate.
+    pub(crate) fn is_drop_mate(&self, piece: Piece, dest: Square) -> bool {
+        self.board_after_drop(piece, dest)
+            .map(|board| board.status() == BoardStatus::Checkmate)
+            .unwrap_or(false)
+    }
+
+    fn update_counters(&mut self, mover: Color, is_zeroing: bool) {
+        if is_zeroing {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if mover == Color::Black {
+            self.fullmove_number += 1;
+        }
+    }
+
+    /// The piece `mv` captures and the square it's captured on.  En passant
+    /// captures a pawn that isn't on the destination square.
+    pub fn get_capture(&self, mv: &BughouseMove) -> Option<(Piece, Square)> {
+        let src = mv.get_source()?;
+        let dest = mv.get_dest();
+        // Chess960 castling moves the king onto its own rook
+        if self.board.color_on(dest) == Some(self.board.side_to_move()) {
+            return None;
+        }
+        if let Some(piece) = self.board.piece_on(dest) {
+            return Some((piece, dest));
+        }
+        if self.board.piece_on(src) == Some(Piece::Pawn)
+            && src.get_file() != dest.get_file()
+        {
+            let sq = Square::make_square(src.get_rank(), dest.get_file());
+            return self.board.piece_on(sq).map(|piece| (piece, sq));
+        }
+        None
     }
 
     pub fn side_to_move(&self) -> Color {
@@ -127,32 +577,248 @@ impl BughouseBoard {
     }
 
     pub fn is_legal(&self, mv: &BughouseMove) -> bool {
-        if mv.get_source() == None {
-            if None == mv.get_piece() {
-                // Invalid drop
-                return false;
-            }
-            // A drop move. Ensure that:
-            // 1. Player to move has the piece in "holdings" or "reserves"
-            // 2. No piece is already there
-            // 3. If it's a pawn, it's only on ranks 2 - 7
-            // 4. Either (a) the player isn't in check, or
-            // 5.        (b) the drop blocks the check
-            let piece = mv.get_piece().unwrap();
-            let bb_sq = BitBoard::from_square(mv.get_dest());
-            self.holdings.has_piece(self.board.side_to_move(), piece)
-                && self.board.piece_on(mv.get_dest()) == None
-                && (piece != Piece::Pawn || bb_sq & *BAD_PAWN_RANKS == EMPTY)
-                && (!self.in_check() || self.blocks_check(bb_sq))
-        } else {
-            // TODO get off this expensive implementation
-            self.board.legal(mv.to_chess_move().unwrap())
+        self.check_move(mv).is_ok()
+    }
+
+    /// Like `is_legal`, but explain why an illegal move is illegal.
+    ///
+    /// ```
+    /// use bughouse::{BughouseBoard, BughouseMove, IllegalMoveReason};
+    /// use chess::{Color, Piece, Square};
+    ///
+    /// let board = BughouseBoard::default();
+    /// let drop = BughouseMove::new(None, Square::E4, Some(Piece::Knight));
+    /// assert_eq!(
+    ///     board.check_move(&drop),
+    ///     Err(IllegalMoveReason::NotHeld(Color::White, Piece::Knight))
+    /// );
+    /// ```
+    pub fn check_move(
+        &self,
+        mv: &BughouseMove,
+    ) -> Result<(), IllegalMoveReason> {
+        let color = self.board.side_to_move();
+        let dest = mv.get_dest();
+        let src = match mv.get_source() {
+            Some(src) => src,
+            None => {
+                // A drop move. Ensure that:
+                // 1. Player to move has the piece in "holdings" or "reserves"
+                // 2. No piece is already there
+                // 3. If it's a pawn, it's on a rank the rules allow
+                // 4. Either (a) the player isn't in check, or
+                // 5.        (b) the drop blocks the check
+                // 6. It doesn't mate, if the rules forbid drop mates
+                let piece = match mv.get_piece() {
+                    Some(Piece::King) | None => {
+                        return Err(IllegalMoveReason::InvalidPieceMove)
+                    }
+                    Some(piece) => piece,
+                };
+                let bb_sq = BitBoard::from_square(dest);
+                return if !self.holdings.has_piece(color, piece) {
+                    Err(IllegalMoveReason::NotHeld(color, piece))
+                } else if self.board.piece_on(dest).is_some() {
+                    Err(IllegalMoveReason::Occupied(dest))
+                } else if piece == Piece::Pawn
+                    && bb_sq & self.rules.get_pawn_drop_squares(color) == EMPTY
+                {
+                    Err(IllegalMoveReason::PawnDropRank)
+                } else if self.in_check() && !self.blocks_check(bb_sq) {
+                    Err(IllegalMoveReason::DropDoesNotResolveCheck)
+                } else if !self.rules.allows_drop_mates()
+                    && self.is_drop_mate(piece, dest)
+                {
+                    Err(IllegalMoveReason::DropMate)
+                } else {
+                    Ok(())
+                };
+            }
+        };
+        if let Some((king, rook)) = self.castle_960(mv) {
+            return self.check_castle(king, rook);
+        }
+        // TODO get off this expensive implementation
+        let chess_mv = mv.to_chess_move().unwrap();
+        if self.board.legal(chess_mv) {
+            return Ok(());
+        }
+        // Work out why not
+        let piece = self
+            .board
+            .piece_on(src)
+            .ok_or(IllegalMoveReason::NoPiece(src))?;
+        if self.board.color_on(src) != Some(color) {
+            return Err(IllegalMoveReason::WrongSideToMove);
         }
+        let promotes = piece == Piece::Pawn
+            && dest.get_rank() == color.to_their_backrank();
+        let valid_promo = match mv.get_piece() {
+            Some(Piece::Pawn) | Some(Piece::King) => false,
+            Some(_) => promotes,
+            None => !promotes,
+        };
+        let reaches = self.reachable(src) & BitBoard::from_square(dest);
+        if !valid_promo || reaches == EMPTY {
+            return Err(IllegalMoveReason::InvalidPieceMove);
+        }
+        Err(IllegalMoveReason::LeavesKingInCheck)
+    }
+
+    // Squares the piece on `src` could move to, ignoring checks and pins
+    fn reachable(&self, src: Square) -> BitBoard {
+        let color = self.board.side_to_move();
+        let combined = *self.board.combined();
+        let targets = match self.board.piece_on(src) {
+            Some(Piece::Pawn) => {
+                let en_passant = match self.board.en_passant() {
+                    Some(sq) => BitBoard::from_square(sq.uforward(color)),
+                    None => EMPTY,
+                };
+                get_pawn_moves(src, color, combined | en_passant)
+            }
+            Some(Piece::Knight) => get_knight_moves(src),
+            Some(Piece::Bishop) => get_bishop_moves(src, combined),
+            Some(Piece::Rook) => get_rook_moves(src, combined),
+            Some(Piece::Queen) => {
+                get_bishop_moves(src, combined) | get_rook_moves(src, combined)
+            }
+            Some(Piece::King) => {
+                let rights = self.board.castle_rights(color);
+                let rank = src.get_rank();
+                let mut castles = EMPTY;
+                if rights.has_kingside()
+                    && rights.kingside_squares(color) & combined == EMPTY
+                {
+                    castles |= BitBoard::set(rank, File::G);
+                }
+                if rights.has_queenside()
+                    && rights.queenside_squares(color) & combined == EMPTY
+                {
+                    castles |= BitBoard::set(rank, File::C);
+                }
+                get_king_moves(src) | castles
+            }
+            None => EMPTY,
+        };
+        targets & !*self.board.color_combined(color)
     }
 
+    /// Serialize to BFEN with 0th rank holdings, the inverse of `from_str`.
+    /// Promoted pieces are suffixed with `~`.
+    ///
+    /// ```
+    /// use bughouse::BughouseBoard;
+    /// use std::str::FromStr;
+    ///
+    /// let bfen = "Q~4rk1/8/8/8/8/8/8/R3K2R/Npb w KQ - 45 60";
+    /// assert_eq!(BughouseBoard::from_str(bfen).unwrap().to_bfen(), bfen);
+    /// ```
     pub fn to_bfen(&self) -> String {
-        // TODO
-        "".to_string()
+        self.to_bfen_as(HoldingsFormat::Slash)
+    }
+
+    /// Serialize to BFEN with the holdings in the given format.
+    ///
+    /// ```
+    /// use bughouse::{BughouseBoard, HoldingsFormat};
+    /// use std::str::FromStr;
+    ///
+    /// let board =
+    ///     BughouseBoard::from_str("4k3/8/8/8/8/8/8/4K3/Qp w - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.to_bfen_as(HoldingsFormat::Bracket),
+    ///     "4k3/8/8/8/8/8/8/4K3[Qp] w - - 0 1"
+    /// );
+    /// assert_eq!(
+    ///     board.to_bfen_as(HoldingsFormat::Suffix),
+    ///     "4k3/8/8/8/8/8/8/4K3 w - - 0 1 Qp"
+    /// );
+    /// ```
+    pub fn to_bfen_as(&self, format: HoldingsFormat) -> String {
+        self.to_bfen_with(format, CastlingNotation::XFen)
+    }
+
+    /// Like `to_bfen_as`, with castling rights in the given notation.
+    ///
+    /// ```
+    /// use bughouse::{BughouseBoard, CastlingNotation, HoldingsFormat};
+    /// use std::str::FromStr;
+    ///
+    /// let bfen = "rk2r3/8/8/8/8/8/8/RK2R3/ w KQkq - 0 1";
+    /// let board = BughouseBoard::from_str(bfen).unwrap();
+    /// assert_eq!(
+    ///     board.to_bfen_with(
+    ///         HoldingsFormat::Slash,
+    ///         CastlingNotation::Shredder
+    ///     ),
+    ///     "rk2r3/8/8/8/8/8/8/RK2R3/ w EAea - 0 1"
+    /// );
+    /// ```
+    pub fn to_bfen_with(
+        &self,
+        format: HoldingsFormat,
+        castling: CastlingNotation,
+    ) -> String {
+        let mut bfen = String::new();
+        for rank in ALL_RANKS.iter().rev() {
+            let mut empty = 0;
+            for file in ALL_FILES.iter() {
+                let sq = Square::make_square(*rank, *file);
+                if let (Some(piece), Some(color)) =
+                    (self.board.piece_on(sq), self.board.color_on(sq))
+                {
+                    if empty > 0 {
+                        bfen.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    bfen.push_str(&piece.to_string(color));
+                    if self.promos.is_promo(color, sq) {
+                        bfen.push('~');
+                    }
+                } else {
+                    empty += 1;
+                }
+            }
+            if empty > 0 {
+                bfen.push_str(&empty.to_string());
+            }
+            if *rank != Rank::First {
+                bfen.push('/');
+            }
+        }
+        let holdings = self.holdings.to_string();
+        match format {
+            // The 8th slash separates the holdings (0th rank)
+            HoldingsFormat::Slash => bfen.push_str(&format!("/{}", holdings)),
+            HoldingsFormat::Bracket => {
+                bfen.push_str(&format!("[{}]", holdings))
+            }
+            HoldingsFormat::Suffix => {}
+        }
+
+        let color = self.board.side_to_move();
+        let castling = self.castling_field(castling);
+        // chess::Board stores the square of the capturable pawn, but FEN wants
+        // the square behind it.
+        let en_passant = match self.board.en_passant() {
+            Some(sq) => sq.ubackward(!color).to_string(),
+            None => "-".to_string(),
+        };
+        let mut bfen = format!(
+            "{} {} {} {} {} {}",
+            bfen,
+            if color == Color::White { "w" } else { "b" },
+            castling,
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number,
+        );
+        if format == HoldingsFormat::Suffix {
+            bfen.push(' ');
+            bfen.push_str(if holdings.is_empty() { "-" } else { &holdings });
+        }
+        bfen
     }
 }
 
@@ -165,6 +831,53 @@ fn mated_but_not_in_bughouse() {
     }
 }
 
+#[test]
+fn mate_statuses() {
+    let all_but_pawns =
+        vec![Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen];
+    let unless = MateStatus::MatedUnless(all_but_pawns);
+    let cases = [
+        ("8/8/8/3k4/8/8/8/4K3/ w - - 0 1", MateStatus::Ongoing),
+        // Interpose on b1 only, so pawns can't help
+        ("3k4/8/8/8/8/8/r7/q1K5/ w - - 0 1", unless.clone()),
+        ("3k4/8/8/8/8/8/r7/q1K5/P w - - 0 1", unless),
+        ("3k4/8/8/8/8/8/r7/q1K5/N w - - 0 1", MateStatus::Blockable),
+        // Interpose on a2-a7
+        (
+            "rr5k/8/8/8/8/8/8/K7/ w - - 0 1",
+            MateStatus::MatedUnless(vec![
+                Piece::Pawn,
+                Piece::Knight,
+                Piece::Bishop,
+                Piece::Rook,
+                Piece::Queen,
+            ]),
+        ),
+        ("rr5k/8/8/8/8/8/8/K7/Pq w - - 0 1", MateStatus::Blockable),
+        ("3k4/8/8/8/8/8/r7/qK6/QRBNP w - - 0 1", MateStatus::Mated),
+        ("3k2r1/8/8/8/8/8/5nr1/7K/Q w - - 0 1", MateStatus::Mated),
+        ("k7/8/8/8/8/8/2q5/K7/ w - - 0 1", MateStatus::MustWait),
+        ("k7/8/8/8/8/8/2q5/K7/pppp w - - 0 1", MateStatus::MustWait),
+        ("k7/8/8/8/8/8/2q5/K7/P w - - 0 1", MateStatus::Ongoing),
+    ];
+    for (bfen, status) in cases.iter() {
+        let board = BughouseBoard::from_str(bfen).unwrap();
+        assert_eq!(board.mate_status(), *status, "{}", bfen);
+        assert_eq!(board.is_mated(), *status == MateStatus::Mated, "{}", bfen);
+    }
+}
+
+#[test]
+fn attackers() {
+    let board = BughouseBoard::default();
+    let expected = BitBoard::from_square(Square::E2)
+        | BitBoard::from_square(Square::G2)
+        | BitBoard::from_square(Square::G1);
+    assert_eq!(board.attackers(Square::F3, Color::White), expected);
+    assert_eq!(board.attackers(Square::F3, Color::Black), EMPTY);
+    assert_eq!(board.attackers(Square::F6, Color::Black).popcnt(), 3);
+}
+
 #[test]
 fn mated_in_bughouse() {
     let mates = [
@@ -177,68 +890,403 @@ fn mated_in_bughouse() {
     }
 }
 
+/// How forgiving BFEN parsing is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum BfenMode {
+    /// Require 8 squares per rank and numeric move counters.
+    Strict,
+    /// Accept `-` or missing move counters (defaulting to 0 and 1), short
+    /// ranks, and an upper case side to move, as found in older files.
+    Lenient,
+}
+
+// Whitespace separated fields, with their (character) offsets
+fn split_fields(input: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
+    for (chars, (i, ch)) in input.char_indices().enumerate() {
+        match (ch.is_whitespace(), start) {
+            (true, Some((offset, begin))) => {
+                fields.push((offset, &input[begin..i]));
+                start = None;
+            }
+            (false, None) => start = Some((chars, i)),
+            _ => {}
+        }
+    }
+    if let Some((offset, begin)) = start {
+        fields.push((offset, &input[begin..]));
+    }
+    fields
+}
+
+impl BughouseBoard {
+    /// Parse one board of BFEN: a FEN with holdings (see `HoldingsFormat`,
+    /// which is detected) and `~` after promoted pieces.  Errors locate the
+    /// offending field and character.
+    ///
+    /// ```
+    /// use bughouse::{BfenField, BfenMode, BughouseBoard, Error};
+    ///
+    /// let bfen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/Nx w KQkq - 0 1";
+    /// match BughouseBoard::from_bfen(bfen, BfenMode::Strict) {
+    ///     Err(Error::BoardParseError { field, offset, .. }) => {
+    ///         assert_eq!(field, BfenField::Holdings);
+    ///         assert_eq!(offset, 45);
+    ///     }
+    ///     _ => panic!("x isn't a piece"),
+    /// }
+    /// ```
+    pub fn from_bfen(input: &str, mode: BfenMode) -> Result<Self, Error> {
+        Self::from_bfen_as(input, mode, HoldingsFormat::detect(input))
+    }
+
+    /// Like `from_bfen`, but with the holdings in the given format rather
+    /// than a detected one.
+    pub fn from_bfen_as(
+        input: &str,
+        mode: BfenMode,
+        format: HoldingsFormat,
+    ) -> Result<Self, Error> {
+        let strict = mode == BfenMode::Strict;
+        let err = |field, offset| Error::BoardParseError {
+            input: input.to_string(),
+            field,
+            offset,
+        };
+        let fields = split_fields(input);
+        let end = input.chars().count();
+        let field = |idx: usize, name| match fields.get(idx) {
+            Some(field) => Ok(*field),
+            None => Err(err(name, end)),
+        };
+
+        // Split off the holdings, inferring empty holdings if there are none
+        let (offset, position) = field(0, BfenField::Placement)?;
+        let no_holdings = (end, "");
+        let (board_part, (holdings_offset, holdings_str)) = match format {
+            HoldingsFormat::Slash if position.matches('/').count() == 8 => {
+                let (board_part, holdings) = position.rsplit_once('/').unwrap();
+                let at = offset + board_part.chars().count() + 1;
+                (board_part, (at, holdings))
+            }
+            HoldingsFormat::Slash => (position, no_holdings),
+            HoldingsFormat::Bracket => match position.split_once('[') {
+                Some((board_part, rest)) => match rest.strip_suffix(']') {
+                    Some(holdings) => {
+                        let at = offset + board_part.chars().count() + 1;
+                        (board_part, (at, holdings))
+                    }
+                    None => {
+                        let at = offset + position.chars().count();
+                        return Err(err(BfenField::Holdings, at));
+                    }
+                },
+                None => (position, no_holdings),
+            },
+            HoldingsFormat::Suffix => match fields.get(6) {
+                Some((_, "-")) | None => (position, no_holdings),
+                Some(holdings) => (position, *holdings),
+            },
+        };
+        if board_part.matches('/').count() != 7 {
+            // Point at the extra slash, or the end of too few ranks
+            let extra = if format == HoldingsFormat::Slash { 8 } else { 7 };
+            let slashes = position.chars().enumerate();
+            let at = match slashes.filter(|(_, c)| *c == '/').nth(extra) {
+                Some((i, _)) => i,
+                None => board_part.chars().count(),
+            };
+            return Err(err(BfenField::Placement, offset + at));
+        }
+        // chess::Board wraps long ranks around and assumes there's one king
+        // of each color, so check both up front
+        let mut squares = 0;
+        let mut kings = [0; 2];
+        for (i, ch) in board_part.chars().enumerate() {
+            match ch {
+                '/' if strict && squares != 8 => {
+                    return Err(err(BfenField::Placement, offset + i))
+                }
+                '/' => squares = 0,
+                '1'..='8' => squares += ch as usize - '0' as usize,
+                'K' | 'k' => {
+                    let king_count = &mut kings[(ch == 'k') as usize];
+                    *king_count += 1;
+                    squares += 1;
+                    if *king_count > 1 {
+                        return Err(err(BfenField::Placement, offset + i));
+                    }
+                }
+                'p' | 'n' | 'b' | 'r' | 'q' | 'P' | 'N' | 'B' | 'R' | 'Q' => {
+                    squares += 1
+                }
+                '~' => {}
+                _ => return Err(err(BfenField::Placement, offset + i)),
+            }
+            if squares > 8 {
+                return Err(err(BfenField::Placement, offset + i));
+            }
+        }
+        let board_end = offset + board_part.chars().count();
+        if (strict && squares != 8) || kings != [1, 1] {
+            return Err(err(BfenField::Placement, board_end));
+        }
+        let promos = Promotions::from_fen(board_part)
+            .map_err(|e| e.shift_bfen_error(input, offset))?;
+        let holdings = Holdings::parse(holdings_str)
+            .map_err(|i| err(BfenField::Holdings, holdings_offset + i))?;
+
+        let (offset, side) = field(1, BfenField::SideToMove)?;
+        match side {
+            "w" | "b" => {}
+            "W" | "B" if !strict => {}
+            _ => return Err(err(BfenField::SideToMove, offset)),
+        }
+        // X-FEN or Shredder-FEN, checked against the placement below
+        let (castling_offset, castling) = field(2, BfenField::Castling)?;
+        if castling != "-" {
+            let bad = castling.chars().position(
+                |c| !matches!(c, 'K' | 'Q' | 'k' | 'q' | 'A'..='H' | 'a'..='h'),
+            );
+            if let Some(i) = bad {
+                return Err(err(BfenField::Castling, castling_offset + i));
+            }
+        }
+        let (offset, en_passant) = field(3, BfenField::EnPassant)?;
+        if en_passant != "-" {
+            let ep_ranks = [Rank::Third, Rank::Sixth];
+            match Square::from_str(en_passant) {
+                Ok(sq) if !strict || ep_ranks.contains(&sq.get_rank()) => {}
+                _ => return Err(err(BfenField::EnPassant, offset)),
+            }
+        }
+        let board_str = format!(
+            "{} {} - {}",
+            board_part.replace('~', ""),
+            side,
+            en_passant
+        );
+        let board = Board::from_str(&board_str)
+            .map_err(|_| err(BfenField::Placement, fields[0].0))?;
+        let mut bug_board = BughouseBoard::new(board, holdings, promos);
+        let rooks = Self::parse_castling(&bug_board.board, castling)
+            .map_err(|i| err(BfenField::Castling, castling_offset + i))?;
+        bug_board.castling_960 = Some(rooks);
+        bug_board
+            .normalize_castling()
+            .map_err(|_| err(BfenField::Castling, castling_offset))?;
+
+        // Anything after the counters (e.g. clock times) besides suffix
+        // holdings is ignored; time is better handled at the server level
+        let counter = |idx, name, default| match fields.get(idx) {
+            Some((_, "-")) | None if !strict => Ok(default),
+            Some((offset, value)) => {
+                value.parse().map_err(|_| err(name, *offset))
+            }
+            None => Err(err(name, end)),
+        };
+        bug_board.halfmove_clock = counter(4, BfenField::HalfmoveClock, 0)?;
+        bug_board.fullmove_number = counter(5, BfenField::FullmoveNumber, 1)?;
+        Ok(bug_board)
+    }
+}
+
+impl BughouseBoard {
+    // The rooks an X-FEN or Shredder-FEN castling field lets each side castle
+    // with, or the index of a right without a king and rook to back it
+    fn parse_castling(
+        board: &Board,
+        castling: &str,
+    ) -> Result<CastlingRooks, usize> {
+        let mut rooks = CastlingRooks::new();
+        if castling == "-" {
+            return Ok(rooks);
+        }
+        for (i, ch) in castling.chars().enumerate() {
+            let color = if ch.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let rank = color.to_my_backrank();
+            let king = board.king_square(color);
+            let home_rooks = board.pieces(Piece::Rook)
+                & board.color_combined(color)
+                & get_rank(rank);
+            let mut files = home_rooks.map(|sq| sq.get_file());
+            let file = match ch.to_ascii_lowercase() {
+                // The outermost rook on that side of the king
+                'k' => files.filter(|file| *file > king.get_file()).last(),
+                'q' => files.find(|file| *file < king.get_file()),
+                letter => {
+                    let file = File::from_index((letter as u8 - b'a') as usize);
+                    let on_file = BitBoard::set(rank, file);
+                    Some(file).filter(|_| home_rooks & on_file != EMPTY)
+                }
+            };
+            match file {
+                Some(file) if king.get_rank() == rank => {
+                    rooks.set(color, file > king.get_file(), file)
+                }
+                _ => return Err(i),
+            }
+        }
+        Ok(rooks)
+    }
+
+    // The castling field, e.g. "KQkq" (or "-")
+    fn castling_field(&self, notation: CastlingNotation) -> String {
+        let mut field = String::new();
+        for color in ALL_COLORS.iter() {
+            let rank = color.to_my_backrank();
+            let home_rooks = self.board.pieces(Piece::Rook)
+                & self.board.color_combined(*color)
+                & get_rank(rank);
+            for kingside in [true, false].iter() {
+                let file = match self.castling_960 {
+                    Some(rooks) => rooks.get(*color, *kingside),
+                    None => {
+                        let rights = self.board.castle_rights(*color);
+                        match kingside {
+                            true if rights.has_kingside() => Some(File::H),
+                            false if rights.has_queenside() => Some(File::A),
+                            _ => None,
+                        }
+                    }
+                };
+                let file = match file {
+                    Some(file) => file,
+                    None => continue,
+                };
+                // Rooks further out on the same side make K/Q ambiguous
+                let outer = home_rooks
+                    .filter(|sq| {
+                        (sq.get_file() > file) == *kingside
+                            && sq.get_file() != file
+                    })
+                    .count();
+                let ch = match notation {
+                    CastlingNotation::XFen if outer == 0 => {
+                        if *kingside {
+                            'k'
+                        } else {
+                            'q'
+                        }
+                    }
+                    _ => (b'a' + file.to_index() as u8) as char,
+                };
+                field.push(match color {
+                    Color::White => ch.to_ascii_uppercase(),
+                    Color::Black => ch,
+                });
+            }
+        }
+        if field.is_empty() {
+            field.push('-');
+        }
+        field
+    }
+}
+
 impl FromStr for BughouseBoard {
     type Err = Error;
 
-    // Parse 0th rank style FEN holdings (like Lichess' Crazyhouse) https://bit.ly/3wx5R3V
-    // Future: Add support for suffix holdings (ala chess.com)?
-    // Expects only 1 board
-    // r2k1r2/pbppNppp/1p2p1nb/1P5N/3N4/4Pn1q/PPP1QP1P/2KR2R1/BrpBBqppN w - - 45 56
-    //   The above ^^^ is only one board, (presplit on " | ")
-    // Dropping support for clock times (in seconds) here as its better handled at the server level
+    /// Leniently parse one board of BFEN (see `from_bfen`), e.g.
+    /// r2k1r2/pbppNppp/1p2p1nb/1P5N/3N4/4Pn1q/PPP1QP1P/2KR2R1/BrpBBqppN w - - 45 56
     fn from_str(input_str: &str) -> Result<Self, Self::Err> {
-        // Tolerate only 7 slashes and infer empty holdings
-        let count = input_str.matches("/").count();
-        if count < 7 || count > 8 {
-            return Err(Error::BoardParseError(input_str.to_string()));
-        }
-        let (bugboard_str, rest) =
-            input_str.split_at(input_str.find(' ').unwrap());
-        let (board_part, holdings_str) = if count == 8 {
-            bugboard_str.rsplit_once('/').unwrap()
+        BughouseBoard::from_bfen(input_str, BfenMode::Lenient)
+    }
+}
+
+const ICS_BORDER: &str = "   +-------------------------------+";
+const ICS_DIVIDER: &str = "   |---+---+---+---+---+---+---+---|";
+
+impl BughouseBoard {
+    /// Lines of an ICS style diagram (see `Display`), from Black's side of
+    /// the board if `flipped`.
+    pub(crate) fn ics_lines(&self, flipped: bool) -> Vec<String> {
+        let mut ranks = ALL_RANKS.to_vec();
+        let mut files = ALL_FILES.to_vec();
+        if flipped {
+            files.reverse();
         } else {
-            (bugboard_str, "")
+            ranks.reverse();
+        }
+        let mut lines = vec![ICS_BORDER.to_string()];
+        for (idx, rank) in ranks.iter().enumerate() {
+            if idx > 0 {
+                lines.push(ICS_DIVIDER.to_string());
+            }
+            let mut line = format!("{}  |", rank.to_index() + 1);
+            for file in files.iter() {
+                let sq = Square::make_square(*rank, *file);
+                let piece = self.board.piece_on(sq);
+                let cell = match (piece, self.board.color_on(sq)) {
+                    (Some(piece), Some(Color::White)) => {
+                        format!(" {} ", piece.to_string(Color::White))
+                    }
+                    (Some(piece), Some(Color::Black)) => {
+                        format!(" *{}", piece.to_string(Color::White))
+                    }
+                    _ => "   ".to_string(),
+                };
+                line.push_str(&cell);
+                line.push('|');
+            }
+            lines.push(line);
+        }
+        lines.push(ICS_BORDER.to_string());
+        let file_names: Vec<String> = files
+            .iter()
+            .map(|file| ((b'a' + file.to_index() as u8) as char).to_string())
+            .collect();
+        lines.push(format!("     {}", file_names.join("   ")));
+
+        let held = |color| {
+            let mut pieces = String::new();
+            for piece in ALL_PIECES[..NUM_HELD_PIECE_TYPES].iter() {
+                for _ in 0..self.holdings.count(color, *piece) {
+                    pieces.push_str(&piece.to_string(Color::White));
+                }
+            }
+            pieces
         };
-        let mut board_str = String::from(board_part.replace('~', ""));
-        board_str.push_str(rest);
-        let holdings = Holdings::from_str(holdings_str).unwrap();
-        let board = Board::from_str(&board_str).unwrap();
-        let promotions = Promotions::from_fen(board_part);
-        Ok(BughouseBoard::new(board, holdings, promotions))
+        lines.push(format!(
+            "   Holdings: White [{}] Black [{}]",
+            held(Color::White),
+            held(Color::Black)
+        ));
+        lines.push(format!("   {:?} to move", self.side_to_move()));
+        lines
     }
 }
 
-// ICS
-//
-//    +-------------------------------+
-// 8  | *R|   |   | *R|   |   | *K|   |
-//    |---+---+---+---+---+---+---+---|
-// 7  | *P| Q |   | *N| *B| *P| *P| *P|     White Moves : 'Bd5     (0:31)'
-//    |---+---+---+---+---+---+---+---|
-// 6  |   |   |   |   | *Q|   |   |   |
-//    |---+---+---+---+---+---+---+---|
-// 5  |   |   |   | B | P |   |   |   |
-//    |---+---+---+---+---+---+---+---|
-// 4  |   |   | P |   |   |   |   |   |
-//    |---+---+---+---+---+---+---+---|
-// 3  |   | P |   |   |   |   |   |   |
-//    |---+---+---+---+---+---+---+---|
-// 2  | P |   |   |   |   | P | P | P |
-//    |---+---+---+---+---+---+---+---|
-// 1  | R |   | B |   | R |   | K |   |
-//    +-------------------------------+
-//      a   b   c   d   e   f   g   h
-// impl fmt::Display for BughouseBoard {
-//     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-//
-//
-//     }
-// }
+/// ICS style, with `*` marking black pieces:
+///
+/// ```text
+///    +-------------------------------+
+/// 8  | *R| *N| *B| *Q| *K| *B| *N| *R|
+///    |---+---+---+---+---+---+---+---|
+/// 7  | *P| *P| *P| *P| *P| *P| *P| *P|
+/// ...
+/// 1  | R | N | B | Q | K | B | N | R |
+///    +-------------------------------+
+///      a   b   c   d   e   f   g   h
+///    Holdings: White [N] Black [PP]
+///    White to move
+/// ```
+impl fmt::Display for BughouseBoard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ics_lines(false).join("\n"))
+    }
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::bughouse_move::get_mv;
+    use crate::bughouse_movegen::BughouseMoveGen;
     use chess::{Color, Piece};
 
     #[test]
@@ -307,12 +1355,433 @@ mod test {
         let expected_holdings = Holdings::new(&[[0, 1, 0, 0, 0], [0; 5]]);
         {
             let holdings = board.holdings();
-            holdings.add(Color::White, Piece::Knight);
+            holdings.add(Color::White, Piece::Knight).unwrap();
             assert!(*holdings == expected_holdings);
         }
         assert!(*board.get_holdings() == expected_holdings);
     }
 
+    #[test]
+    fn bfen_round_trip() {
+        let bfens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ w KQkq - 0 1",
+            "r2k1r2/pbppNppp/1p2p1nb/1P5N/3N4/4Pn1q/PPP1QP1P/2KR2R1/NBBBppprq w - - 45 56",
+            "Q~4rk1/8/8/8/8/8/8/R3K2R/ b K - 3 60",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR/Pp w KQkq f6 0 3",
+        ];
+        for bfen in &bfens {
+            let board = BughouseBoard::from_str(bfen).unwrap();
+            assert_eq!(board.to_bfen(), *bfen);
+            // And every position one ply away
+            for mv in BughouseMoveGen::new_legal(&board) {
+                let mut next = board.clone();
+                next.make_move(&mv).unwrap();
+                let next_bfen = next.to_bfen();
+                assert_eq!(BughouseBoard::from_str(&next_bfen).unwrap(), next);
+            }
+        }
+    }
+
+    #[test]
+    fn bfen_tracks_moves() {
+        let mut board = BughouseBoard::from_str(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/Nn w KQkq - 0 1",
+        )
+        .unwrap();
+        for (mv, bfen) in &[
+            (
+                "e2e4",
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR/Nn b KQkq - 0 1",
+            ),
+            (
+                "n@e6",
+                "rnbqkbnr/pppppppp/4n3/8/4P3/8/PPPP1PPP/RNBQKBNR/N w KQkq - 1 2",
+            ),
+            (
+                "e4e5",
+                "rnbqkbnr/pppppppp/4n3/4P3/8/8/PPPP1PPP/RNBQKBNR/N b KQkq - 0 2",
+            ),
+            (
+                "d7d5",
+                "rnbqkbnr/ppp1pppp/4n3/3pP3/8/8/PPPP1PPP/RNBQKBNR/N w KQkq d6 0 3",
+            ),
+        ] {
+            board.make_move(&get_mv(mv)).unwrap();
+            assert_eq!(board.to_bfen(), *bfen);
+            assert_eq!(BughouseBoard::from_str(bfen).unwrap(), board);
+        }
+    }
+
+    #[test]
+    fn hash_is_incremental() {
+        let start = "r3k2r/6P1/8/8/8/8/8/R3K2R/Qn w KQkq - 0 1";
+        let mut board = BughouseBoard::from_str(start).unwrap();
+        let initial_hash = board.get_hash();
+        let moves = ["g7h8q", "n@f8", "Q@e2", "e8d7", "h8f8", "a8a1", "e1f2"];
+        for mv in &moves {
+            board.make_move(&get_mv(mv)).unwrap();
+            let parsed = BughouseBoard::from_str(&board.to_bfen()).unwrap();
+            assert_eq!(board.get_hash(), parsed.get_hash(), "{}", mv);
+        }
+        assert_ne!(board.get_hash(), initial_hash);
+
+        // Knight moves back and forth repeat the position
+        let mut board = BughouseBoard::default();
+        let hash = board.get_hash();
+        for mv in &["g1f3", "g8f6", "f3g1", "f6g8"] {
+            board.make_move(&get_mv(mv)).unwrap();
+        }
+        assert_eq!(board.get_hash(), hash);
+    }
+
+    #[test]
+    fn unmake_restores_the_board() {
+        for (start, moves) in &[
+            (
+                "r3k2r/6P1/8/8/8/8/8/R3K2R/Qn w KQkq - 0 1",
+                &["g7h8q", "n@f8", "Q@e2", "e8d7", "h8f8", "a8a1", "e1f2"][..],
+            ),
+            (
+                "rk2r3/pppppppp/8/8/8/8/PPPPPPPP/RK2R3/N w KQkq - 0 1",
+                &["b1e1", "b8a8", "N@c3", "b7b5", "c3b5"][..],
+            ),
+            (
+                "4k3/8/8/8/3Pp3/8/2P5/1N2K3/N b - d3 0 2",
+                &["e4d3", "N@c3", "d3c2", "c3b5", "c2b1q"][..],
+            ),
+        ] {
+            let mut board = BughouseBoard::from_str(start).unwrap();
+            let mut played = Vec::new();
+            for mv in moves.iter() {
+                let before = board.clone();
+                played.push((before, board.make_move(&get_mv(mv)).unwrap()));
+            }
+            for (before, undo) in played.into_iter().rev() {
+                board.unmake_move(undo).unwrap();
+                assert_eq!(board, before);
+                assert_eq!(board.to_bfen(), before.to_bfen());
+                assert_eq!(board.get_hash(), before.get_hash());
+            }
+        }
+
+        let start = "r3k3/6P1/8/8/8/8/8/4K3/n w - - 0 1";
+        let mut board = BughouseBoard::from_str(start).unwrap();
+        let undo = board.make_move(&get_mv("g7g8q")).unwrap();
+        assert_eq!(undo.get_captured(), None);
+        let undo = board.make_move(&get_mv("n@f8")).unwrap();
+        assert_eq!(undo.get_dropped(), Some(Piece::Knight));
+        let undo = board.make_move(&get_mv("g8f8")).unwrap();
+        assert_eq!(undo.get_captured(), Some((Piece::Knight, Square::F8)));
+        assert_eq!(undo.get_dropped(), None);
+
+        // A hand filled since the drop has no room to take the piece back
+        let start = "4k3/8/8/8/8/8/8/4K3/N w - - 0 1";
+        let mut board = BughouseBoard::from_str(start).unwrap();
+        let undo = board.make_move(&get_mv("N@e4")).unwrap();
+        for _ in 0..u8::MAX {
+            board.holdings().add(Color::White, Piece::Knight).unwrap();
+        }
+        let full = board.clone();
+        assert!(matches!(
+            board.unmake_move(undo),
+            Err(Error::HoldingsOverflow(Color::White, Piece::Knight))
+        ));
+        assert_eq!(board, full);
+        board.holdings().drop(Color::White, Piece::Knight).unwrap();
+        board.unmake_move(undo).unwrap();
+        let held = board.get_holdings().count(Color::White, Piece::Knight);
+        assert_eq!(held, u8::MAX);
+    }
+
+    #[test]
+    fn hash_covers_holdings_and_promos() {
+        let hash =
+            |bfen: &str| BughouseBoard::from_str(bfen).unwrap().get_hash();
+        let board = "4k3/8/8/8/8/8/8/Q3K3/";
+        let hashes = [
+            hash(&format!("{} w - - 0 1", board)),
+            hash(&format!("{}N w - - 0 1", board)),
+            hash(&format!("{}NN w - - 0 1", board)),
+            hash(&format!("{}n w - - 0 1", board)),
+            hash("4k3/8/8/8/8/8/8/Q~3K3/ w - - 0 1"),
+            hash(&format!("{} b - - 0 1", board)),
+        ];
+        for (i, a) in hashes.iter().enumerate() {
+            for b in &hashes[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+        // Counters aren't part of the position
+        assert_eq!(hashes[0], hash(&format!("{} w - - 12 40", board)));
+    }
+
+    #[test]
+    fn illegal_move_reasons() {
+        use IllegalMoveReason::*;
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ w KQkq - 0 1";
+        let holding_n =
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/N w KQkq - 0 1";
+        let in_check = "4k3/8/8/8/8/8/8/4K2r/P w - - 0 1";
+        let cases = [
+            (start, "e7e5", WrongSideToMove),
+            (start, "e3e4", NoPiece(Square::E3)),
+            (start, "e2e5", InvalidPieceMove),
+            (start, "d1d2", InvalidPieceMove),
+            (start, "e1g1", InvalidPieceMove),
+            (start, "N@e4", NotHeld(Color::White, Piece::Knight)),
+            (holding_n, "N@e2", Occupied(Square::E2)),
+            ("4k3/8/8/8/8/8/8/4K3/P w - - 0 1", "P@a1", PawnDropRank),
+            (in_check, "P@e4", DropDoesNotResolveCheck),
+            (in_check, "e1f1", LeavesKingInCheck),
+            // Pinned
+            ("4k3/4r3/8/8/8/8/4B3/4K3/ w - - 0 1", "e2d3", LeavesKingInCheck),
+            // Castling out of check
+            ("4k3/8/8/8/8/8/8/R3K2r/ w Q - 0 1", "e1c1", LeavesKingInCheck),
+            // Promotions must name a piece
+            ("4k3/P7/8/8/8/8/8/4K3/ w - - 0 1", "a7a8", InvalidPieceMove),
+        ];
+        for (bfen, mv, reason) in &cases {
+            let board = BughouseBoard::from_str(bfen).unwrap();
+            let mv = get_mv(mv);
+            assert_eq!(board.check_move(&mv), Err(*reason), "{} {}", bfen, mv);
+            assert!(!board.is_legal(&mv));
+            assert!(matches!(
+                board.clone().make_move(&mv),
+                Err(Error::IllegalMove(_, r)) if r == *reason
+            ));
+        }
+        let board = BughouseBoard::from_str(cases[12].0).unwrap();
+        assert_eq!(board.check_move(&get_mv("a7a8q")), Ok(()));
+    }
+
+    #[test]
+    fn rules() {
+        use IllegalMoveReason::*;
+        let board = BughouseBoard::from_str("4k3/8/8/8/8/8/8/4K3/P w - - 0 1");
+        let mut board = board.unwrap();
+        let chessgi = Rules::new().with_pawn_drop_ranks(&[
+            Rank::First,
+            Rank::Second,
+            Rank::Third,
+            Rank::Fourth,
+            Rank::Fifth,
+            Rank::Sixth,
+            Rank::Seventh,
+        ]);
+        board.set_rules(chessgi);
+        assert_eq!(board.check_move(&get_mv("P@a1")), Ok(()));
+        assert_eq!(board.check_move(&get_mv("P@a8")), Err(PawnDropRank));
+        assert_eq!(BughouseMoveGen::new_legal(&board).len(), 5 + 55);
+
+        let no_seventh = Rules::new().with_pawn_drop_ranks(&[
+            Rank::Second,
+            Rank::Third,
+            Rank::Fourth,
+            Rank::Fifth,
+            Rank::Sixth,
+        ]);
+        board.set_rules(no_seventh);
+        assert_eq!(board.check_move(&get_mv("P@a7")), Err(PawnDropRank));
+        assert_eq!(board.check_move(&get_mv("P@a6")), Ok(()));
+        assert_eq!(BughouseMoveGen::new_legal(&board).len(), 5 + 40);
+
+        // A rook drop on a8-f8 mates (but the king could take one on g8)
+        let bfen = "7k/6pp/8/8/8/8/8/4K3/R w - - 0 1";
+        let mut board = BughouseBoard::from_str(bfen).unwrap();
+        assert_eq!(board.check_move(&get_mv("R@a8")), Ok(()));
+        let moves = BughouseMoveGen::new_legal(&board).len();
+        board.set_rules(Rules::new().with_drop_mates(false));
+        assert_eq!(board.check_move(&get_mv("R@a8")), Err(DropMate));
+        assert_eq!(board.check_move(&get_mv("R@a7")), Ok(()));
+        assert_eq!(BughouseMoveGen::new_legal(&board).len(), moves - 6);
+
+        // Only a pawn drop on b1 would block
+        let bfen = "3k4/8/8/8/8/8/r7/q1K5/P w - - 0 1";
+        let mut board = BughouseBoard::from_str(bfen).unwrap();
+        assert!(matches!(board.mate_status(), MateStatus::MatedUnless(_)));
+        board.set_rules(chessgi);
+        assert_eq!(board.mate_status(), MateStatus::Blockable);
+    }
+
+    #[test]
+    fn bfen_errors_locate_the_problem() {
+        use BfenField::*;
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/";
+        let no_kings = "8/8/8/8/8/8/8/8/ w - - 0 1";
+        let two_kings = "8/8/8/8/8/8/8/k1k1K3/ w - - 0 1";
+        // Black is in check, but it's White's move
+        let not_to_move_checked = "k6R/8/8/8/8/8/8/K7/ w - - 0 1";
+        let cases = [
+            (String::new(), Placement, 0),
+            ("rnbqkbnr/pppppppp/8/8 w - - 0 1".to_string(), Placement, 21),
+            ("8/8/8/8/8/8/8/8/8/8 w - - 0 1".to_string(), Placement, 17),
+            (
+                format!("{} w KQkq - 0 1", start.replace("RNBQ", "RNXQ")),
+                Placement,
+                37,
+            ),
+            (
+                format!("{} w KQkq - 0 1", start.replace("RNBQ", "~NBQ")),
+                Placement,
+                35,
+            ),
+            (format!("{}Pk w KQkq - 0 1", start), Holdings, 45),
+            (start.to_string(), SideToMove, 44),
+            (format!("{} x KQkq - 0 1", start), SideToMove, 45),
+            (format!("{} w KQxq - 0 1", start), Castling, 49),
+            (format!("{} w KQkq e9 0 1", start), EnPassant, 52),
+            (format!("{} w KQkq - x 1", start), HalfmoveClock, 54),
+            (format!("{} w KQkq - 0 -1", start), FullmoveNumber, 56),
+            (no_kings.to_string(), Placement, 15),
+            (two_kings.to_string(), Placement, 16),
+            (not_to_move_checked.to_string(), Placement, 0),
+        ];
+        for (bfen, expected_field, expected_offset) in &cases {
+            match BughouseBoard::from_str(bfen) {
+                Err(Error::BoardParseError { field, offset, input }) => {
+                    assert_eq!(field, *expected_field, "{}", bfen);
+                    assert_eq!(offset, *expected_offset, "{}", bfen);
+                    assert_eq!(input, *bfen);
+                }
+                res => panic!("{}: {:?}", bfen, res),
+            }
+        }
+    }
+
+    #[test]
+    fn strict_and_lenient_bfen() {
+        let lenient_only = [
+            "4k3/8/8/8/8/8/8/4K3/ w - - - -",
+            "4k3/8/8/8/8/8/8/4K3/ w - -",
+            "4k3/8/8/8/8/8/8/4K3/ W - - 0 1",
+            "4k3/8/8/8/8/8/r/4K3/ w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3/ w - e4 0 1",
+        ];
+        for bfen in &lenient_only {
+            let board = BughouseBoard::from_str(bfen).unwrap();
+            assert!(BughouseBoard::from_bfen(bfen, BfenMode::Strict).is_err());
+            if bfen.ends_with('-') {
+                assert_eq!(board.get_halfmove_clock(), 0);
+                assert_eq!(board.get_fullmove_number(), 1);
+            }
+        }
+        let bfen = "4k3/8/8/8/8/8/8/4K3/Qp w - - 3 9 180 175";
+        let board = BughouseBoard::from_bfen(bfen, BfenMode::Strict).unwrap();
+        assert_eq!(board.get_fullmove_number(), 9);
+    }
+
+    #[test]
+    fn malformed_bfen_never_panics() {
+        let bfen = concat!(
+            "r2k1r2/pbppNppp/1p2p1nb/1P5N/3N4/4Pn1q/PPP1QP1P/2K~R2R1/BrpBBqppN",
+            " w Kq e3 45 56"
+        );
+        let replacements = ['/', ' ', '~', '-', '9', '0', 'K', 'x', 'é', '1'];
+        for (i, _) in bfen.char_indices() {
+            let _ = BughouseBoard::from_str(&bfen[..i]);
+            let _ = BughouseBoard::from_str(&bfen[i + 1..]);
+            for ch in &replacements {
+                let mut mutated = bfen.to_string();
+                mutated.replace_range(i..i + 1, &ch.to_string());
+                let _ = BughouseBoard::from_str(&mutated);
+                let _ = BughouseBoard::from_bfen(&mutated, BfenMode::Strict);
+            }
+        }
+        let many_pawns =
+            format!("4k3/8/8/8/8/8/8/4K3/{} w - - 0 1", "P".repeat(300));
+        assert!(BughouseBoard::from_str(&many_pawns).is_err());
+    }
+
+    #[test]
+    fn holdings_formats() {
+        let slash = "r3k2r/8/8/8/8/8/8/R3K2R/NQp w KQkq - 0 1";
+        let bracket = "r3k2r/8/8/8/8/8/8/R3K2R[NQp] w KQkq - 0 1";
+        let suffix = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1 NQp";
+        let board = BughouseBoard::from_str(slash).unwrap();
+        for (bfen, format) in &[
+            (slash, HoldingsFormat::Slash),
+            (bracket, HoldingsFormat::Bracket),
+            (suffix, HoldingsFormat::Suffix),
+        ] {
+            assert_eq!(BughouseBoard::from_str(bfen).unwrap(), board);
+            let parsed =
+                BughouseBoard::from_bfen_as(bfen, BfenMode::Strict, *format);
+            assert_eq!(parsed.unwrap(), board);
+            assert_eq!(board.to_bfen_as(*format), *bfen);
+        }
+
+        let empty = BughouseBoard::default();
+        for format in &[
+            HoldingsFormat::Slash,
+            HoldingsFormat::Bracket,
+            HoldingsFormat::Suffix,
+        ] {
+            let bfen = empty.to_bfen_as(*format);
+            assert_eq!(BughouseBoard::from_str(&bfen).unwrap(), empty);
+        }
+        assert!(empty.to_bfen_as(HoldingsFormat::Suffix).ends_with(" 1 -"));
+        assert!(empty.to_bfen_as(HoldingsFormat::Bracket).contains("R[] w"));
+
+        // Promoted pieces and Lichess' brackets together
+        let board =
+            BughouseBoard::from_str("4k3/8/8/8/8/8/8/Q~3K3[Pn] w - - 0 1")
+                .unwrap();
+        assert!(board.get_promos().is_promo(Color::White, Square::A1));
+        assert!(board.get_holdings().has_piece(Color::Black, Piece::Knight));
+
+        let unclosed = "4k3/8/8/8/8/8/8/4K3[Pn w - - 0 1";
+        assert!(matches!(
+            BughouseBoard::from_str(unclosed),
+            Err(Error::BoardParseError {
+                field: BfenField::Holdings,
+                offset: 22,
+                ..
+            })
+        ));
+        let bad_suffix = "4k3/8/8/8/8/8/8/4K3 w - - 0 1 Pk";
+        assert!(BughouseBoard::from_bfen_as(
+            bad_suffix,
+            BfenMode::Lenient,
+            HoldingsFormat::Suffix
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn ics_display() {
+        let board = BughouseBoard::from_str(
+            "r2r2k1/pQ1nbppp/4q3/3BP3/2P5/1P6/P4PPP/R1B1R1K1/NQp b - - 0 20",
+        )
+        .unwrap();
+        let expected = [
+            "   +-------------------------------+",
+            "8  | *R|   |   | *R|   |   | *K|   |",
+            "   |---+---+---+---+---+---+---+---|",
+            "7  | *P| Q |   | *N| *B| *P| *P| *P|",
+            "   |---+---+---+---+---+---+---+---|",
+            "6  |   |   |   |   | *Q|   |   |   |",
+            "   |---+---+---+---+---+---+---+---|",
+            "5  |   |   |   | B | P |   |   |   |",
+            "   |---+---+---+---+---+---+---+---|",
+            "4  |   |   | P |   |   |   |   |   |",
+            "   |---+---+---+---+---+---+---+---|",
+            "3  |   | P |   |   |   |   |   |   |",
+            "   |---+---+---+---+---+---+---+---|",
+            "2  | P |   |   |   |   | P | P | P |",
+            "   |---+---+---+---+---+---+---+---|",
+            "1  | R |   | B |   | R |   | K |   |",
+            "   +-------------------------------+",
+            "     a   b   c   d   e   f   g   h",
+            "   Holdings: White [NQ] Black [P]",
+            "   Black to move",
+        ];
+        assert_eq!(board.to_string(), expected.join("\n"));
+
+        let flipped = board.ics_lines(true);
+        assert_eq!(flipped[1], "1  |   | K |   | R |   | B |   | R |");
+        assert_eq!(flipped[17], "     h   g   f   e   d   c   b   a");
+    }
+
     #[test]
     fn test_drops_blocks_check() {
         let cases = [
@@ -325,4 +1794,99 @@ mod test {
             assert!(board.blocks_check(bb) == *expected);
         }
     }
+
+    #[test]
+    fn chess960_castling() {
+        use IllegalMoveReason::*;
+        let bfen = "rk2r3/pppppppp/8/8/8/8/PPPPPPPP/RK2R3/N w KQkq - 0 1";
+        let board = BughouseBoard::from_str(bfen).unwrap();
+        let shredder =
+            "rk2r3/pppppppp/8/8/8/8/PPPPPPPP/RK2R3/N w EAea - 0 1";
+        assert_eq!(BughouseBoard::from_str(shredder).unwrap(), board);
+        assert_eq!(board.to_bfen(), bfen);
+        let moves: Vec<BughouseMove> =
+            BughouseMoveGen::new_legal(&board).collect();
+        assert!(moves.contains(&get_mv("b1e1")));
+        assert!(moves.contains(&get_mv("b1a1")));
+
+        // The king lands on g1 (c1) and the rook on f1 (d1)
+        let kingside = BughouseMove::from_ban(&board, "O-O").unwrap();
+        assert_eq!(kingside, get_mv("b1e1"));
+        assert_eq!(kingside.to_ban(&board), "O-O");
+        assert_eq!(board.get_capture(&kingside), None);
+        let mut castled = board.clone();
+        castled.make_move(&kingside).unwrap();
+        assert_eq!(
+            castled.to_bfen(),
+            "rk2r3/pppppppp/8/8/8/8/PPPPPPPP/R4RK1/N b kq - 1 1"
+        );
+        let mut castled = board.clone();
+        castled.make_move(&get_mv("b1a1")).unwrap();
+        assert_eq!(
+            castled.to_bfen(),
+            "rk2r3/pppppppp/8/8/8/8/PPPPPPPP/2KRR3/N b kq - 1 1"
+        );
+
+        // A dropped piece blocks castling through it
+        let blocked = "rk2r3/8/8/8/8/8/8/RK1nR3/ w KQkq - 0 1";
+        let board = BughouseBoard::from_str(blocked).unwrap();
+        assert_eq!(board.check_move(&get_mv("b1e1")), Err(InvalidPieceMove));
+        assert_eq!(board.check_move(&get_mv("b1a1")), Err(InvalidPieceMove));
+
+        // The king can't cross an attacked square, even the rook's
+        let attacked = "rk2r3/8/8/8/8/8/8/RK2R3/ w KQkq - 0 1";
+        let board = BughouseBoard::from_str(attacked).unwrap();
+        assert_eq!(board.check_move(&get_mv("b1e1")), Err(LeavesKingInCheck));
+        assert_eq!(board.check_move(&get_mv("b1a1")), Ok(()));
+
+        // A rook dropped back on its square doesn't restore the right
+        let mut board = BughouseBoard::from_str(attacked).unwrap();
+        for mv in ["e1e2", "a8a7", "e2e3", "a7a8"].iter() {
+            board.make_move(&get_mv(mv)).unwrap();
+        }
+        board.holdings().add(Color::White, Piece::Rook).unwrap();
+        board.make_move(&get_mv("R@e1")).unwrap();
+        assert!(board.to_bfen().contains(" b Qk "), "{}", board);
+        assert!(BughouseBoard::from_str("4k3/8/8/8/8/8/8/4K3 w K - 0 1")
+            .is_err());
+    }
+
+    #[test]
+    fn chess960_rights_become_classical() {
+        let bfen = "4k3/8/8/8/8/8/8/RR2K2R/ w KB - 0 1";
+        let mut board = BughouseBoard::from_str(bfen).unwrap();
+        assert!(board.get_castling_rooks().is_some());
+        assert_eq!(
+            board.to_bfen_with(HoldingsFormat::Slash, CastlingNotation::XFen),
+            bfen
+        );
+        board.make_move(&get_mv("b1b2")).unwrap();
+        board.make_move(&get_mv("e8d8")).unwrap();
+        // King on e1, rook on h1: classical castling from here
+        assert!(board.get_castling_rooks().is_none());
+        let classical =
+            BughouseBoard::from_str("3k4/8/8/8/8/8/1R6/R3K2R/ w K - 2 2");
+        assert_eq!(board, classical.unwrap());
+        assert!(board.is_legal(&get_mv("e1g1")));
+        assert!(!board.is_legal(&get_mv("e1h1")));
+    }
+
+    #[test]
+    fn chess960_setups() {
+        for id in [0, 959].iter() {
+            let board = BughouseBoard::chess960(*id).unwrap();
+            let rooks = board.get_castling_rooks().unwrap();
+            let back_rank = chess960_back_rank(*id).unwrap();
+            for color in ALL_COLORS.iter() {
+                for kingside in [true, false].iter() {
+                    let file = rooks.get(*color, *kingside).unwrap();
+                    assert_eq!(back_rank[file.to_index()], Piece::Rook);
+                }
+            }
+        }
+        assert_eq!(BughouseBoard::chess960(960), None);
+        let mut rooks = CastlingRooks::new();
+        rooks.set(Color::White, false, File::A);
+        assert_ne!(rooks.get_hash(), 0);
+    }
 }
@@ -1,9 +1,21 @@
-use crate::bughouse_board::BughouseBoard;
+use crate::bughouse_board::{BfenMode, BughouseBoard};
 use crate::bughouse_move::BughouseMove;
+use crate::capture_routing::CaptureRouting;
+use crate::chess960::{CastlingNotation, NUM_CHESS960_POSITIONS};
+use crate::clock::Clocks;
 use crate::error::*;
-use chess::Piece;
-use std::str::FromStr;
+use crate::game_result::{BughouseResult, Termination};
+use crate::holdings::{HoldingsFormat, NUM_HELD_PIECE_TYPES};
+use crate::move_outcome::MoveOutcome;
+use crate::multi_bughouse_game::MultiBughouseGame;
+use crate::rules::Rules;
+use crate::zobrist::next_key;
+use chess::{
+    get_rank, Color, Piece, ALL_COLORS, ALL_PIECES, NUM_COLORS, NUM_PIECES,
+};
 use std::fmt;
+use std::ops::Not;
+use std::str::FromStr;
 
 #[derive(PartialEq, Eq, Ord, PartialOrd, Copy, Clone, Debug, Hash)]
 pub enum BoardID {
@@ -28,10 +40,215 @@ impl BoardID {
     }
 }
 
+/// A board in a game of any number of boards (see `MultiBughouseGame`),
+/// lettered from A.
+#[derive(PartialEq, Eq, Ord, PartialOrd, Copy, Clone, Hash)]
+pub struct MultiBoardID(u8);
+
+/// The most boards a game can have, one per letter.
+pub const MAX_BOARDS: usize = 26;
+
+impl fmt::Display for MultiBoardID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", (b'A' + self.0) as char)
+    }
+}
+
+impl fmt::Debug for MultiBoardID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl MultiBoardID {
+    pub const A: MultiBoardID = MultiBoardID(0);
+    pub const B: MultiBoardID = MultiBoardID(1);
+
+    /// The board at `index` (A is 0), if it's under `MAX_BOARDS`.
+    #[inline]
+    pub fn new(index: usize) -> Option<Self> {
+        if index < MAX_BOARDS {
+            Some(MultiBoardID(index as u8))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn to_index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// How a game names its boards: `BoardID` in bughouse and crazyhouse, and
+/// `MultiBoardID` in a `MultiBughouseGame`.
+pub trait GameBoardID: Copy + Eq + fmt::Debug + fmt::Display {
+    /// The board at `index`, which must be one these IDs can name.
+    fn from_index(index: usize) -> Self;
+
+    fn to_index(&self) -> usize;
+
+    /// The same board, named as in a `MultiBughouseGame`.
+    #[inline]
+    fn to_multi(&self) -> MultiBoardID {
+        MultiBoardID(self.to_index() as u8)
+    }
+}
+
+impl GameBoardID for BoardID {
+    #[inline]
+    fn from_index(index: usize) -> Self {
+        BOARD_IDS[index]
+    }
+
+    #[inline]
+    fn to_index(&self) -> usize {
+        BoardID::to_index(self)
+    }
+}
+
+impl GameBoardID for MultiBoardID {
+    #[inline]
+    fn from_index(index: usize) -> Self {
+        MultiBoardID(index as u8)
+    }
+
+    #[inline]
+    fn to_index(&self) -> usize {
+        MultiBoardID::to_index(self)
+    }
+}
+
+/// The two partnerships.  Team One is White on board A and Black on board B
+/// (the "1-0" side of a BPGN result), Team Two is Black on A and White on B.
+/// With more boards, colors keep alternating: Team One is White on C, E, etc.
+/// and Black on D, F, etc.
+#[derive(PartialEq, Eq, Ord, PartialOrd, Copy, Clone, Debug, Hash)]
+pub enum Team {
+    One,
+    Two,
+}
+
+impl Team {
+    /// The two seats on this team, board A's first.
+    pub fn seats(&self) -> [Seat; 2] {
+        match self {
+            Team::One => [
+                Seat::new(BoardID::A, Color::White),
+                Seat::new(BoardID::B, Color::Black),
+            ],
+            Team::Two => [
+                Seat::new(BoardID::A, Color::Black),
+                Seat::new(BoardID::B, Color::White),
+            ],
+        }
+    }
+}
+
+impl Not for Team {
+    type Output = Team;
+
+    fn not(self) -> Team {
+        match self {
+            Team::One => Team::Two,
+            Team::Two => Team::One,
+        }
+    }
+}
+
+impl fmt::Display for Team {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Team {}", if *self == Team::One { "One" } else { "Two" })
+    }
+}
+
+/// One of the four players: a color on a board.  A `MultiSeat` is a player
+/// in a game of any number of boards.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub struct Seat<B = BoardID> {
+    board: B,
+    color: Color,
+}
+
+pub type MultiSeat = Seat<MultiBoardID>;
+
+pub const ALL_SEATS: [Seat; 4] = [
+    Seat::new(BoardID::A, Color::White),
+    Seat::new(BoardID::A, Color::Black),
+    Seat::new(BoardID::B, Color::White),
+    Seat::new(BoardID::B, Color::Black),
+];
+
+impl<B> Seat<B> {
+    #[inline]
+    pub const fn new(board: B, color: Color) -> Self {
+        Seat { board, color }
+    }
+}
+
+impl<B: GameBoardID> Seat<B> {
+    #[inline]
+    pub fn get_board(&self) -> B {
+        self.board
+    }
+
+    #[inline]
+    pub fn get_color(&self) -> Color {
+        self.color
+    }
+
+    /// The team this seat plays for.
+    #[inline]
+    pub fn team(&self) -> Team {
+        if self.board.to_index() % 2 == self.color.to_index() {
+            Team::One
+        } else {
+            Team::Two
+        }
+    }
+
+    /// The player across the board.
+    #[inline]
+    pub fn opponent(&self) -> Self {
+        Seat::new(self.board, !self.color)
+    }
+
+    /// Convert the `Seat` to a `usize` for table lookups.
+    #[inline]
+    pub fn to_index(&self) -> usize {
+        self.board.to_index() * 2 + self.color.to_index()
+    }
+
+    /// The same player, seated as in a `MultiBughouseGame`.
+    #[inline]
+    pub fn to_multi(&self) -> MultiSeat {
+        Seat::new(self.board.to_multi(), self.color)
+    }
+}
+
+impl Seat {
+    /// The teammate on the other board, who plays the opposite color and
+    /// receives this seat's captures.
+    #[inline]
+    pub fn partner(&self) -> Seat {
+        Seat::new(BOARD_IDS[1 - self.board.to_index()], !self.color)
+    }
+}
+
+/// BPGN header style, e.g. `WhiteA` or `BlackB`
+impl<B: fmt::Display> fmt::Display for Seat<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}{}", self.color, self.board)
+    }
+}
+
 /// A representation of one Bughouse board.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct BughouseGame {
-    boards: [BughouseBoard; 2],
+    // Boards A and B, each passing to the other
+    game: MultiBughouseGame<BoardID>,
+    clocks: Option<Clocks>,
+    players: [Option<String>; 4],
 }
 
 impl Default for BughouseGame {
@@ -43,47 +260,329 @@ impl Default for BughouseGame {
 
 impl BughouseGame {
     pub fn new(a: BughouseBoard, b: BughouseBoard) -> Self {
-        BughouseGame { boards: [a, b] }
+        let routing = CaptureRouting::round_robin(2);
+        BughouseGame {
+            game: MultiBughouseGame::from_parts(vec![a, b], routing),
+            clocks: None,
+            players: Default::default(),
+        }
+    }
+
+    /// A Chess960 game from starting positions `a` and `b` (see
+    /// `chess960_back_rank`).  `None` if either is out of range.
+    pub fn chess960(a: u16, b: u16) -> Option<Self> {
+        Some(BughouseGame::new(
+            BughouseBoard::chess960(a)?,
+            BughouseBoard::chess960(b)?,
+        ))
+    }
+
+    /// A Chess960 game with starting positions drawn from `seed`, the same
+    /// on both boards if `same_setup`.  The same seed always gives the same
+    /// game.
+    ///
+    /// ```
+    /// use bughouse::{BoardID, BughouseGame};
+    ///
+    /// let game = BughouseGame::chess960_seeded(1_000_000, true);
+    /// assert_eq!(game, BughouseGame::chess960_seeded(1_000_000, true));
+    /// assert_eq!(
+    ///     game.get_board(BoardID::A),
+    ///     game.get_board(BoardID::B),
+    /// );
+    /// ```
+    pub fn chess960_seeded(seed: u64, same_setup: bool) -> Self {
+        let mut state = seed;
+        let mut draw = || {
+            (next_key(&mut state) % NUM_CHESS960_POSITIONS as u64) as u16
+        };
+        let a = draw();
+        let b = if same_setup { a } else { draw() };
+        BughouseGame::chess960(a, b).unwrap()
+    }
+
+    /// Attach clocks, which `make_move` will then switch after every move.
+    pub fn set_clocks(&mut self, clocks: Clocks) {
+        self.clocks = Some(clocks);
+    }
+
+    #[inline]
+    pub fn get_clocks(&self) -> Option<&Clocks> {
+        self.clocks.as_ref()
+    }
+
+    /// Start White's clock on both boards (if clocks are attached).
+    pub fn start_clocks(&mut self) {
+        if let Some(clocks) = self.clocks.as_mut() {
+            clocks.start();
+        }
+    }
+
+    /// Play both boards by `rules` (see `BughouseBoard::set_rules`).
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.game.set_rules(rules);
+    }
+
+    /// Board A's rules, which `set_rules` keeps the same as board B's.
+    #[inline]
+    pub fn get_rules(&self) -> &Rules {
+        self.game.get_boards()[0].get_rules()
     }
 
     pub fn get_board(&self, id: BoardID) -> &BughouseBoard {
-        &self.boards[id.to_index()]
+        self.game.board(id)
+    }
+
+    /// Hash of both boards (see `BughouseBoard::get_hash`).  Swapping the
+    /// boards changes the hash.
+    pub fn get_hash(&self) -> u64 {
+        let boards = self.game.get_boards();
+        boards[0].get_hash() ^ boards[1].get_hash().rotate_left(32)
+    }
+
+    /// Serialize both boards as `"<board A BFEN> | <board B BFEN>"`, the
+    /// inverse of `from_str`.
+    pub fn to_bfen(&self) -> String {
+        self.to_bfen_as(HoldingsFormat::Slash)
     }
 
-    // TODO
-    // pub fn is_sane(&self) -> bool {
-    // }
+    /// Like `to_bfen`, with the holdings in the given format.
+    pub fn to_bfen_as(&self, format: HoldingsFormat) -> String {
+        self.to_bfen_with(format, CastlingNotation::XFen)
+    }
 
+    /// Like `to_bfen_as`, with castling rights in the given notation.
+    pub fn to_bfen_with(
+        &self,
+        format: HoldingsFormat,
+        castling: CastlingNotation,
+    ) -> String {
+        self.game.to_bfen_with(format, castling)
+    }
+
+    /// Check that the position could have arisen from play: the boards and
+    /// holdings together hold no more of each color than two chess sets
+    /// have (counting promoted pieces as the pawns they were), and no board
+    /// has pawns where the rules don't allow them.  Meant for vetting
+    /// positions from elsewhere before play; parsing already rejects boards
+    /// without one king per side or with the side not to move in check.
+    ///
+    /// When promoted pieces keep their identity on capture (see
+    /// `Rules::with_promotions_revert`), promoted pieces in hand can't be
+    /// told apart, so pieces beyond two sets count against the pawns.
+    ///
+    /// ```
+    /// use bughouse::{BughouseGame, SanityViolation};
+    /// use chess::{Color, Piece};
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(BughouseGame::default().is_sane(), Ok(()));
+    /// let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
+    /// let bfen = format!("{}/QQ w - - 0 1 | {}/q w - - 0 1", start, start);
+    /// assert_eq!(
+    ///     BughouseGame::from_str(&bfen).unwrap().is_sane(),
+    ///     Err(vec![
+    ///         SanityViolation::TooManyPieces(Color::White, Piece::Queen, 4),
+    ///         SanityViolation::TooManyPieces(Color::Black, Piece::Queen, 3),
+    ///     ])
+    /// );
+    /// ```
+    pub fn is_sane(&self) -> Result<(), Vec<SanityViolation>> {
+        // One side of a full set; pieces keep their color when passed, so
+        // each color has two of these
+        const SIDE: [u32; NUM_PIECES] = [8, 2, 2, 2, 1, 1];
+        let mut violations = Vec::new();
+        let mut counts = [[0u32; NUM_PIECES]; NUM_COLORS];
+        for (id, bug_board) in BOARD_IDS.iter().zip(self.game.get_boards()) {
+            let board = bug_board.get_board();
+            for color in ALL_COLORS.iter() {
+                for piece in ALL_PIECES[..NUM_HELD_PIECE_TYPES].iter() {
+                    let held = bug_board.get_holdings().count(*color, *piece);
+                    counts[color.to_index()][piece.to_index()] +=
+                        u32::from(held);
+                }
+            }
+            for sq in *board.combined() {
+                let color = board.color_on(sq).unwrap();
+                let piece = if bug_board.get_promos().is_promo(color, sq) {
+                    Piece::Pawn
+                } else {
+                    board.piece_on(sq).unwrap()
+                };
+                counts[color.to_index()][piece.to_index()] += 1;
+            }
+            let rules = bug_board.get_rules();
+            for color in ALL_COLORS.iter() {
+                // Pawns are never on the last rank, nor on their own back rank
+                // unless they can be dropped there
+                let back_ranks = get_rank(color.to_my_backrank())
+                    | get_rank(color.to_their_backrank());
+                let allowed = (!back_ranks
+                    | rules.get_pawn_drop_squares(*color))
+                    & !get_rank(color.to_their_backrank());
+                let pawns =
+                    board.pieces(Piece::Pawn) & board.color_combined(*color);
+                for sq in pawns & !allowed {
+                    violations.push(SanityViolation::PawnOnBackRank(*id, sq));
+                }
+            }
+        }
+        for color in ALL_COLORS.iter() {
+            let counts = &mut counts[color.to_index()];
+            if !self.get_rules().promotions_revert() {
+                // Any surplus must have been promoted from pawns
+                for piece in ALL_PIECES[1..NUM_HELD_PIECE_TYPES].iter() {
+                    let idx = piece.to_index();
+                    let surplus = counts[idx].saturating_sub(2 * SIDE[idx]);
+                    counts[idx] -= surplus;
+                    counts[Piece::Pawn.to_index()] += surplus;
+                }
+            }
+            for piece in ALL_PIECES.iter() {
+                let count = counts[piece.to_index()];
+                if count > 2 * SIDE[piece.to_index()] {
+                    violations.push(SanityViolation::TooManyPieces(
+                        *color, *piece, count,
+                    ));
+                }
+            }
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Identify the player in `seat` (a user name, id, etc).
+    pub fn set_player(&mut self, seat: Seat, player: &str) {
+        self.players[seat.to_index()] = Some(player.to_string());
+    }
+
+    pub fn get_player(&self, seat: Seat) -> Option<&str> {
+        self.players[seat.to_index()].as_deref()
+    }
+
+    /// The seat whose turn it is on `board`.
+    pub fn seat_to_move(&self, board: BoardID) -> Seat {
+        self.game.to_move(board)
+    }
+
+    /// How the game ended, if it has.
+    #[inline]
+    pub fn get_result(&self) -> Option<&BughouseResult> {
+        self.game.get_result()
+    }
+
+    #[inline]
+    pub fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    /// End the game if any player has run out of time.  Servers should poll
+    /// this, as nobody may be moving when a flag falls.
+    pub fn check_flags(&mut self) -> Option<&BughouseResult> {
+        if !self.game.is_over() {
+            let flagged = self.clocks.as_ref().and_then(|c| c.flagged());
+            if let Some(seat) = flagged {
+                self.end(BughouseResult::lost(seat, Termination::Flag));
+            }
+        }
+        self.game.get_result()
+    }
+
+    /// `seat` resigns for their team.
+    pub fn resign(&mut self, seat: Seat) -> Result<(), Error> {
+        self.try_end(BughouseResult::lost(seat, Termination::Resignation))
+    }
+
+    pub fn abort(&mut self) -> Result<(), Error> {
+        self.try_end(BughouseResult::aborted())
+    }
+
+    pub fn agree_draw(&mut self) -> Result<(), Error> {
+        self.try_end(BughouseResult::drawn())
+    }
+
+    fn try_end(&mut self, result: BughouseResult) -> Result<(), Error> {
+        if let Some(result) = self.game.get_result() {
+            return Err(Error::GameOver(result.to_multi()));
+        }
+        self.end(result);
+        Ok(())
+    }
+
+    fn end(&mut self, result: BughouseResult) {
+        self.game.end(result);
+        if let Some(clocks) = self.clocks.as_mut() {
+            clocks.stop();
+        }
+    }
+
+    /// Play `mv` as `seat`, rejecting it if it isn't their turn.
+    pub fn make_seat_move(
+        &mut self,
+        seat: Seat,
+        mv: &BughouseMove,
+    ) -> Result<MoveOutcome, Error> {
+        if self.seat_to_move(seat.board) != seat {
+            return Err(Error::OutOfTurn(seat.to_multi()));
+        }
+        self.make_move(seat.board, mv)
+    }
+
+    /// Play `mv` on board `name`, passing any captured piece to the partner.
+    /// Fails once the game is over; a mate on either board ends it.
     pub fn make_move(
         &mut self,
         name: BoardID,
         mv: &BughouseMove,
-    ) -> Result<(), Error> {
-        let bug_board = &mut self.boards[name.to_index()];
-        let chess_board = bug_board.get_board();
-        let dest = mv.get_dest();
-        let captured_piece = chess_board.piece_on(dest);
-        let opp = !chess_board.side_to_move();
-        let is_promo = bug_board.get_promos().is_promo(opp, dest);
-        bug_board.make_move(mv)?;
-        if let Some(piece) = captured_piece {
-            let other_board = &mut self.boards[1 - name.to_index()];
-            other_board
-                .holdings()
-                .add(opp, if is_promo { Piece::Pawn } else { piece });
+    ) -> Result<MoveOutcome, Error> {
+        if let Some(result) = self.check_flags() {
+            return Err(Error::GameOver(result.to_multi()));
+        }
+        let outcome = self.game.play(name, mv)?;
+        if let Some(clocks) = self.clocks.as_mut() {
+            clocks.record_move(outcome.get_seat());
+            if self.game.is_over() {
+                clocks.stop();
+            }
         }
-        return Ok(());
+        Ok(outcome)
     }
-}
 
-impl FromStr for BughouseGame {
-    type Err = Error;
+    /// Every move played through `make_move`, in order, across both boards.
+    #[inline]
+    pub fn get_history(&self) -> &[MoveOutcome] {
+        self.game.get_history()
+    }
 
+    /// The most recent move played on `board`, if any.
+    pub fn get_last_move(&self, board: BoardID) -> Option<&BughouseMove> {
+        self.game.get_last_move(board)
+    }
+
+    /// Take back the last move played on either board, restoring that board,
+    /// its promotions, and the partner's holdings (removing the piece the
+    /// move sent them).  A game ended by the move is resumed.  Clocks are not
+    /// rewound.  Returns the move taken back, if there was one.
+    pub fn unmake_move(&mut self) -> Option<MoveOutcome> {
+        self.game.unmake_move()
+    }
+}
+
+impl BughouseGame {
+    /// Parse `"<board A BFEN> | <board B BFEN>"`, detecting each board's
+    /// holdings format.  Errors in board B are located relative to the whole
+    /// input.
     /// Note: ignores time input, so flagging will not be handled.
-    fn from_str(input_str: &str) -> Result<Self, Self::Err> {
+    pub fn from_bfen(input_str: &str, mode: BfenMode) -> Result<Self, Error> {
         if let Some((a_str, b_str)) = input_str.split_once(" | ") {
-            let board_a = BughouseBoard::from_str(a_str)?;
-            let board_b = BughouseBoard::from_str(b_str)?;
+            let board_a = BughouseBoard::from_bfen(a_str, mode)
+                .map_err(|e| e.shift_bfen_error(input_str, 0))?;
+            let shift = a_str.chars().count() + 3;
+            let board_b = BughouseBoard::from_bfen(b_str, mode)
+                .map_err(|e| e.shift_bfen_error(input_str, shift))?;
             Ok(BughouseGame::new(board_a, board_b))
         } else {
             Err(Error::GameParseError(format!("Invalid ' | ' split: {}", input_str)))
@@ -91,28 +590,173 @@ impl FromStr for BughouseGame {
     }
 }
 
-// Pretty print each board
-// impl fmt::Display for BughouseMove {
-//     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-//
-//     }
-// }
+impl FromStr for BughouseGame {
+    type Err = Error;
+
+    /// Leniently parse both boards (see `BughouseGame::from_bfen`).
+    fn from_str(input_str: &str) -> Result<Self, Self::Err> {
+        BughouseGame::from_bfen(input_str, BfenMode::Lenient)
+    }
+}
+
+/// Both boards' ICS diagrams side by side, with board B flipped so each
+/// team's players appear next to each other, as partners see it.
+impl fmt::Display for BughouseGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let boards = self.game.get_boards();
+        let a = boards[0].ics_lines(false);
+        let b = boards[1].ics_lines(true);
+        let width = a.iter().map(|line| line.len()).max().unwrap_or(0);
+        // Line the titles up with the boards' left borders
+        let titles =
+            format!("   {:w$}       Board B", "Board A", w = width - 3);
+        writeln!(f, "{}", titles)?;
+        let lines: Vec<String> = a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| format!("{:width$}    {}", a, b, width = width))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::bughouse_move::get_mv;
+    use crate::clock::{ManualTime, TimeControl};
+    use crate::move_outcome::HoldingsDelta;
     use crate::Holdings;
     use crate::Promotions;
-    use chess::{BitBoard, Square, EMPTY};
+    use chess::{BitBoard, Color, Rank, Square, EMPTY};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn sanity() {
+        let insane = |bfen: &str| {
+            BughouseGame::from_str(bfen).unwrap().is_sane().unwrap_err()
+        };
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
+        let bfen = format!("{}/ w - - 0 1 | {}/ w - - 0 1", start, start);
+        assert_eq!(BughouseGame::from_str(&bfen).unwrap().is_sane(), Ok(()));
+
+        // Everything captured and in hand
+        let bare = "4k3/8/8/8/8/8/8/4K3";
+        let all = "PPPPPPPPNNBBRRQppppppppnnbbrrq";
+        let b = format!("{}/{} w - - 0 1", bare, all);
+        let bfen = format!("{}/{} w - - 0 1 | {}", bare, all, b);
+        assert_eq!(BughouseGame::from_str(&bfen).unwrap().is_sane(), Ok(()));
+        let bfen = format!("{}/{}p w - - 0 1 | {}", bare, all, b);
+        assert_eq!(
+            insane(&bfen),
+            vec![SanityViolation::TooManyPieces(Color::Black, Piece::Pawn, 17)]
+        );
+        // Right in total, but not per color
+        let swapped = all.replacen('p', "P", 1);
+        let bfen = format!("{}/{} w - - 0 1 | {}", bare, swapped, b);
+        assert_eq!(
+            insane(&bfen),
+            vec![SanityViolation::TooManyPieces(Color::White, Piece::Pawn, 17)]
+        );
+
+        // Promoted queens were pawns
+        let promoted = "4k3/8/8/8/8/8/8/Q~Q~Q~1K3/ w - - 0 1";
+        let bfen = format!("{} | {}/ w - - 0 1", promoted, start);
+        assert_eq!(BughouseGame::from_str(&bfen).unwrap().is_sane(), Ok(()));
+        let unpromoted = promoted.replace('~', "");
+        let bare = "4k3/8/8/8/8/8/8/4K3/ w - - 0 1";
+        let bfen = format!("{} | {}/ w - - 0 1", unpromoted, start);
+        assert_eq!(
+            insane(&bfen),
+            vec![SanityViolation::TooManyPieces(Color::White, Piece::Queen, 4)]
+        );
+
+        let pawns = "P3k3/8/8/8/8/8/8/4K2p/ w - - 0 1";
+        let bfen = format!("{} | {}/ w - - 0 1", pawns, start);
+        assert_eq!(
+            insane(&bfen),
+            vec![
+                SanityViolation::PawnOnBackRank(BoardID::A, Square::A8),
+                SanityViolation::PawnOnBackRank(BoardID::A, Square::H1),
+            ]
+        );
+
+        // Pawns on their own 1st rank are fine in Chessgi, but never on the
+        // 8th
+        let mut game = BughouseGame::from_str(&bfen).unwrap();
+        game.set_rules(chessgi());
+        assert_eq!(game.is_sane().unwrap_err().len(), 2);
+        let pawn = "4k3/8/8/8/8/8/8/P3K3/ w - - 0 1";
+        let bfen = format!("{} | {}/ w - - 0 1", pawn, start);
+        let mut game = BughouseGame::from_str(&bfen).unwrap();
+        game.set_rules(chessgi());
+        assert_eq!(game.is_sane(), Ok(()));
+
+        // Captured promoted queens may be held as queens
+        let bfen = format!("{} | {}/QQQ w - - 0 1", bare, start);
+        let mut game = BughouseGame::from_str(&bfen).unwrap();
+        assert_eq!(
+            game.is_sane(),
+            Err(vec![SanityViolation::TooManyPieces(
+                Color::White,
+                Piece::Queen,
+                4
+            )])
+        );
+        game.set_rules(Rules::new().with_promotions_revert(false));
+        assert_eq!(game.is_sane(), Ok(()));
+
+        // Parsing already rules out missing kings and checks on the side not
+        // to move
+        for board in &["4k3/8/8/8/8/8/8/8/", "4k3/8/8/8/8/8/8/4R2K/"] {
+            let bfen = format!("{} w - - 0 1 | {}/ w - - 0 1", board, start);
+            assert!(BughouseGame::from_str(&bfen).is_err());
+        }
+    }
+
+    fn chessgi() -> Rules {
+        Rules::new().with_pawn_drop_ranks(&[
+            Rank::First,
+            Rank::Second,
+            Rank::Third,
+            Rank::Fourth,
+            Rank::Fifth,
+            Rank::Sixth,
+            Rank::Seventh,
+        ])
+    }
+
+    #[test]
+    fn promoted_pieces_keep_identity() {
+        let bfen = "q~3k3/8/8/8/8/8/8/R3K3/ w - - 0 1 | \
+                    rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ w KQkq - 0 1";
+        let mut game = BughouseGame::from_str(bfen).unwrap();
+        let mut identity = game.clone();
+        let outcome = game.make_move(BoardID::A, &get_mv("a1a8")).unwrap();
+        assert_eq!(
+            outcome.get_transferred(),
+            Some((Seat::new(BoardID::B, Color::Black), Piece::Pawn))
+        );
+
+        identity.set_rules(Rules::new().with_promotions_revert(false));
+        let outcome = identity.make_move(BoardID::A, &get_mv("a1a8")).unwrap();
+        let seat = Seat::new(BoardID::B, Color::Black);
+        assert_eq!(outcome.get_transferred(), Some((seat, Piece::Queen)));
+        let holdings = identity.get_board(BoardID::B).get_holdings();
+        assert_eq!(holdings.count(Color::Black, Piece::Queen), 1);
+        identity.unmake_move();
+        let holdings = identity.get_board(BoardID::B).get_holdings();
+        assert_eq!(holdings.count(Color::Black, Piece::Queen), 0);
+    }
 
     #[test]
     fn opening_game() {
         let mut game = BughouseGame::default();
-        println!("beg a: {:?}", game.boards[0]);
+        println!("beg a: {:?}", game.get_board(BoardID::A));
         game.make_move(BoardID::A, &get_mv("e2e4")).unwrap();
-        println!("end a: {:?}", game.boards[0]);
-        let color = game.boards[0].get_board().side_to_move();
+        println!("end a: {:?}", game.get_board(BoardID::A));
+        let color = game.get_board(BoardID::A).get_board().side_to_move();
         assert!(color == chess::Color::Black);
         game.make_move(BoardID::A, &get_mv("e7e5")).unwrap();
     }
@@ -137,21 +781,22 @@ mod test {
             (BoardID::B, get_mv("d8d5")), // Qxd5
         ];
         for (name, mv) in &moves {
-            game.make_move(*name, &mv).unwrap();
+            game.make_move(*name, mv).unwrap();
         }
-        assert!(!game.boards[0].is_mated());
+        assert!(!game.get_board(BoardID::A).is_mated());
         // Each white player has a pawn
         let expected_holdings = Holdings::new(&[[1, 0, 0, 0, 0]; 2]);
-        assert!(*game.boards[0].get_holdings() == expected_holdings);
+        let holdings = game.get_board(BoardID::A).get_holdings();
+        assert!(*holdings == expected_holdings);
         assert!(game.make_move(BoardID::A, &get_mv("P@f7")).is_ok());
-        assert!(game.boards[0].is_mated());
+        assert!(game.get_board(BoardID::A).is_mated());
     }
 
     #[test]
     fn tracking_promos() {
         let bfen = format!(
             "{} | {}",
-            "4k3/7P/8/q78/8/PPPPPPP/RNBQKBNR/ w - - - -",
+            "4k3/7P/8/q7/8/8/PPPPPPP1/RNBQKBNR/ w - - - -",
             "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/nnbbrrpppppppp w - - - -",
             );
         let mut game = BughouseGame::from_str(&bfen).unwrap();
@@ -179,4 +824,409 @@ mod test {
         let expected_promos = Promotions::new(&[EMPTY, EMPTY]);
         assert!(*game.get_board(BoardID::A).get_promos() == expected_promos);
     }
+
+    #[test]
+    fn make_move_switches_clocks() {
+        let time = ManualTime::new();
+        let tc = TimeControl::new(Duration::from_secs(60), Duration::default());
+        let mut game = BughouseGame::default();
+        game.set_clocks(Clocks::new(tc, Arc::new(time.clone())));
+        game.start_clocks();
+        time.advance(Duration::from_secs(3));
+        game.make_move(BoardID::A, &get_mv("e2e4")).unwrap();
+        time.advance(Duration::from_secs(4));
+        game.make_move(BoardID::B, &get_mv("d2d4")).unwrap();
+        time.advance(Duration::from_secs(5));
+        let clocks = game.get_clocks().unwrap();
+        let remaining = |board, color| {
+            clocks.remaining(Seat::new(board, color)).as_secs()
+        };
+        assert_eq!(remaining(BoardID::A, Color::White), 57);
+        assert_eq!(remaining(BoardID::A, Color::Black), 51);
+        assert_eq!(remaining(BoardID::B, Color::White), 53);
+        assert_eq!(remaining(BoardID::B, Color::Black), 55);
+        assert_eq!(clocks.running(BoardID::B), Some(Color::Black));
+
+        // Illegal moves don't touch the clocks
+        assert!(game.make_move(BoardID::B, &get_mv("d4d5")).is_err());
+        assert_eq!(game.get_clocks().unwrap().running(BoardID::B), Some(Color::Black));
+    }
+
+    #[test]
+    fn mate_ends_the_game() {
+        let mut game = BughouseGame::default();
+        for (name, mv) in &[
+            (BoardID::A, "f2f3"),
+            (BoardID::A, "e7e5"),
+            (BoardID::A, "g2g4"),
+        ] {
+            game.make_move(*name, &get_mv(mv)).unwrap();
+        }
+        // Fool's mate can be blocked with a drop, so it's not over
+        game.make_move(BoardID::A, &get_mv("d8h4")).unwrap();
+        assert!(!game.is_over());
+
+        let bfen = format!(
+            "{} | {}",
+            "kr6/pp6/8/1N6/8/8/8/K7/ w - - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ w KQkq - 0 1",
+        );
+        let mut game = BughouseGame::from_str(&bfen).unwrap();
+        game.make_move(BoardID::A, &get_mv("b5c7")).unwrap();
+        let result = *game.get_result().unwrap();
+        assert_eq!(result.get_reason(), Termination::Checkmate);
+        assert_eq!(result.get_loser(), Some(Seat::new(BoardID::A, Color::Black)));
+        assert_eq!(result.get_winner(), Some(Team::One));
+        assert_eq!(result.to_bpgn_result(), "1-0");
+        assert_eq!(
+            result.to_string(),
+            "Team One wins: Black on board A lost by checkmate"
+        );
+        // No more moves on either board
+        assert!(matches!(
+            game.make_move(BoardID::B, &get_mv("d7d5")),
+            Err(Error::GameOver(_))
+        ));
+        assert!(game.resign(Seat::new(BoardID::B, Color::Black)).is_err());
+    }
+
+    #[test]
+    fn flag_ends_the_game() {
+        let time = ManualTime::new();
+        let tc = TimeControl::new(Duration::from_secs(60), Duration::default());
+        let mut game = BughouseGame::default();
+        game.set_clocks(Clocks::new(tc, Arc::new(time.clone())));
+        game.start_clocks();
+        time.advance(Duration::from_secs(5));
+        game.make_move(BoardID::A, &get_mv("e2e4")).unwrap();
+        time.advance(Duration::from_secs(70));
+        let result = *game.check_flags().unwrap();
+        assert_eq!(result.get_reason(), Termination::Flag);
+        // Both are out of time, but Board B's White ran out 5s before
+        // Black on A
+        let white_b = Seat::new(BoardID::B, Color::White);
+        assert_eq!(result.get_loser(), Some(white_b));
+        assert_eq!(result.get_winner(), Some(Team::One));
+        assert!(game.make_move(BoardID::A, &get_mv("e7e5")).is_err());
+    }
+
+    #[test]
+    fn resign_abort_and_draw() {
+        let mut game = BughouseGame::default();
+        game.resign(Seat::new(BoardID::B, Color::White)).unwrap();
+        assert_eq!(game.get_result().unwrap().get_winner(), Some(Team::One));
+        assert!(game.abort().is_err());
+
+        let mut game = BughouseGame::default();
+        game.agree_draw().unwrap();
+        assert_eq!(game.get_result().unwrap().get_winner(), None);
+        assert_eq!(game.get_result().unwrap().to_bpgn_result(), "1/2-1/2");
+
+        let mut game = BughouseGame::default();
+        game.abort().unwrap();
+        assert_eq!(game.get_result().unwrap().to_bpgn_result(), "*");
+    }
+
+    #[test]
+    fn seats_and_teams() {
+        let white_a = Seat::new(BoardID::A, Color::White);
+        let black_b = Seat::new(BoardID::B, Color::Black);
+        assert_eq!(white_a.team(), Team::One);
+        assert_eq!(black_b.team(), Team::One);
+        assert_eq!(white_a.partner(), black_b);
+        assert_eq!(black_b.partner(), white_a);
+        assert_eq!(white_a.opponent(), Seat::new(BoardID::A, Color::Black));
+        assert_eq!(white_a.opponent().team(), Team::Two);
+        assert_eq!(Team::Two.seats()[1].to_string(), "WhiteB");
+        for (idx, seat) in ALL_SEATS.iter().enumerate() {
+            assert_eq!(seat.to_index(), idx);
+            assert!(seat.team().seats().contains(seat));
+            assert_eq!(seat.partner().partner(), *seat);
+        }
+    }
+
+    #[test]
+    fn seat_moves() {
+        let mut game = BughouseGame::default();
+        let white_a = Seat::new(BoardID::A, Color::White);
+        game.set_player(white_a, "alice");
+        assert_eq!(game.get_player(white_a), Some("alice"));
+        let black_b = white_a.partner();
+        assert_eq!(game.get_player(black_b), None);
+
+        game.make_seat_move(white_a, &get_mv("e2e4")).unwrap();
+        assert!(matches!(
+            game.make_seat_move(white_a, &get_mv("d2d4")),
+            Err(Error::OutOfTurn(_))
+        ));
+        // Black on B isn't on move yet either
+        assert!(game.make_seat_move(black_b, &get_mv("e7e5")).is_err());
+        assert_eq!(game.seat_to_move(BoardID::A), white_a.opponent());
+        game.make_seat_move(white_a.opponent(), &get_mv("d7d5")).unwrap();
+        game.make_seat_move(white_a, &get_mv("e4d5")).unwrap();
+        // White A's capture lands in Black B's holdings
+        let holdings = game.get_board(BoardID::B).get_holdings();
+        assert!(holdings.has_piece(black_b.get_color(), Piece::Pawn));
+    }
+
+    #[test]
+    fn move_outcomes() {
+        let bfen = format!(
+            "{} | {}",
+            "4k3/7P/8/q7/8/8/PPPPPPP1/RNBQKBNR/N w KQ - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ w KQkq - 0 1",
+        );
+        let mut game = BughouseGame::from_str(&bfen).unwrap();
+        let white_a = Seat::new(BoardID::A, Color::White);
+        let black_a = white_a.opponent();
+        let white_b = Seat::new(BoardID::B, Color::White);
+
+        let outcome = game.make_move(BoardID::A, &get_mv("h7h8q")).unwrap();
+        assert_eq!(outcome.get_seat(), white_a);
+        assert!(outcome.is_check());
+        assert!(!outcome.is_mate());
+        assert_eq!(outcome.get_captured(), None);
+        assert!(outcome.get_holdings_deltas().is_empty());
+
+        game.make_move(BoardID::A, &get_mv("e8e7")).unwrap();
+        game.make_move(BoardID::A, &get_mv("h8h5")).unwrap();
+        // Promoted queen goes to the partner as a pawn
+        let outcome = game.make_move(BoardID::A, &get_mv("a5h5")).unwrap();
+        assert_eq!(outcome.get_seat(), black_a);
+        assert_eq!(outcome.get_captured(), Some((Piece::Queen, Square::H5)));
+        assert_eq!(
+            outcome.get_transferred(),
+            Some((white_b, Piece::Pawn))
+        );
+        assert_eq!(
+            outcome.get_holdings_deltas(),
+            &[HoldingsDelta::new(white_b, Piece::Pawn, 1)]
+        );
+
+        let outcome = game.make_move(BoardID::A, &get_mv("N@g6")).unwrap();
+        assert!(outcome.is_check());
+        assert_eq!(
+            outcome.get_holdings_deltas(),
+            &[HoldingsDelta::new(white_a, Piece::Knight, -1)]
+        );
+    }
+
+    #[test]
+    fn en_passant_captures_transfer() {
+        let mut game = BughouseGame::default();
+        for mv in &["e2e4", "a7a6", "e4e5", "d7d5"] {
+            game.make_move(BoardID::A, &get_mv(mv)).unwrap();
+        }
+        let outcome = game.make_move(BoardID::A, &get_mv("e5d6")).unwrap();
+        assert_eq!(outcome.get_captured(), Some((Piece::Pawn, Square::D5)));
+        let black_b = Seat::new(BoardID::B, Color::Black);
+        assert_eq!(outcome.get_transferred(), Some((black_b, Piece::Pawn)));
+        assert_eq!(
+            *game.get_board(BoardID::B).get_holdings(),
+            Holdings::new(&[[0; 5], [1, 0, 0, 0, 0]])
+        );
+    }
+
+    #[test]
+    fn unmake_moves() {
+        let bfen = format!(
+            "{} | {}",
+            "4k3/7P/8/q7/8/8/PPPPPPP1/RNBQKBNR/ w KQ - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ w KQkq - 0 1",
+        );
+        let start = BughouseGame::from_str(&bfen).unwrap();
+        let mut game = start.clone();
+        assert!(game.unmake_move().is_none());
+        let moves = [
+            (BoardID::A, "h7h8q"),
+            (BoardID::A, "e8e7"),
+            (BoardID::A, "h8h5"),
+            // Captures a promoted queen, sending White on B a pawn
+            (BoardID::A, "a5h5"),
+            (BoardID::B, "P@e3"),
+            (BoardID::B, "e7e5"),
+        ];
+        let mut positions = vec![game.to_bfen()];
+        for (name, mv) in &moves {
+            game.make_move(*name, &get_mv(mv)).unwrap();
+            positions.push(game.to_bfen());
+        }
+        assert_eq!(game.get_history().len(), moves.len());
+        assert_eq!(game.get_history()[4].get_move(), &get_mv("P@e3"));
+
+        positions.pop();
+        while let Some(bfen) = positions.pop() {
+            let outcome = game.unmake_move().unwrap();
+            assert_eq!(outcome.get_move(), &get_mv(moves[positions.len()].1));
+            assert_eq!(game.to_bfen(), bfen);
+        }
+        assert_eq!(game, start);
+        assert!(game.get_history().is_empty());
+    }
+
+    #[test]
+    fn game_hash() {
+        let mut game = BughouseGame::default();
+        let hash = game.get_hash();
+        game.make_move(BoardID::A, &get_mv("e2e4")).unwrap();
+        let a_moved = game.get_hash();
+        assert_ne!(a_moved, hash);
+        game.unmake_move().unwrap();
+        assert_eq!(game.get_hash(), hash);
+        // The same move on the other board is a different game position
+        game.make_move(BoardID::B, &get_mv("e2e4")).unwrap();
+        assert_ne!(game.get_hash(), a_moved);
+    }
+
+    #[test]
+    fn unmake_resumes_the_game() {
+        let bfen = format!(
+            "{} | {}",
+            "kr6/pp6/8/1N6/8/8/8/K7/ w - - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ w KQkq - 0 1",
+        );
+        let mut game = BughouseGame::from_str(&bfen).unwrap();
+        game.make_move(BoardID::A, &get_mv("b5c7")).unwrap();
+        assert!(game.is_over());
+        game.unmake_move().unwrap();
+        assert!(!game.is_over());
+        assert_eq!(game.to_bfen(), bfen);
+        game.make_move(BoardID::B, &get_mv("e2e4")).unwrap();
+    }
+
+    #[test]
+    fn bfen_errors_on_board_b() {
+        let board = "4k3/8/8/8/8/8/8/4K3/";
+        let bfen = format!("{} w - - 0 1 | {} w - - x 1", board, board);
+        match BughouseGame::from_str(&bfen) {
+            Err(Error::BoardParseError { field, offset, input }) => {
+                assert_eq!(field, BfenField::HalfmoveClock);
+                assert_eq!(offset, 60);
+                assert_eq!(input, bfen);
+                assert_eq!(&bfen[offset..offset + 1], "x");
+            }
+            res => panic!("{:?}", res),
+        }
+        let bfen = format!("{} w - - - - | {} w - - 0 1", board, board);
+        assert!(BughouseGame::from_str(&bfen).is_ok());
+        assert!(BughouseGame::from_bfen(&bfen, BfenMode::Strict).is_err());
+        assert!(matches!(
+            BughouseGame::from_str(board),
+            Err(Error::GameParseError(_))
+        ));
+    }
+
+    #[test]
+    fn side_by_side_display() {
+        let mut game = BughouseGame::default();
+        game.make_move(BoardID::A, &get_mv("e2e4")).unwrap();
+        game.make_move(BoardID::A, &get_mv("d7d5")).unwrap();
+        game.make_move(BoardID::A, &get_mv("e4d5")).unwrap();
+        let display = game.to_string();
+        let lines: Vec<&str> = display.lines().collect();
+        assert_eq!(lines.len(), 21);
+        assert_eq!(
+            lines[0],
+            format!("   Board A{}Board B", " ".repeat(33))
+        );
+        // Board B is seen from Black's side
+        assert_eq!(
+            lines[2],
+            "8  | *R| *N| *B| *Q| *K| *B| *N| *R|    1  | R | N | B | K | Q | B | N | R |"
+        );
+        assert_eq!(
+            lines[6],
+            "6  |   |   |   |   |   |   |   |   |    3  |   |   |   |   |   |   |   |   |"
+        );
+        assert_eq!(
+            lines[8],
+            "5  |   |   |   | P |   |   |   |   |    4  |   |   |   |   |   |   |   |   |"
+        );
+        // Board B's lines start after the widest of board A's, plus 4
+        assert_eq!(
+            lines[19],
+            format!(
+                "{:40}{}",
+                "   Holdings: White [] Black []",
+                "   Holdings: White [] Black [P]"
+            )
+        );
+        assert_eq!(
+            lines[20],
+            format!("{:40}{}", "   Black to move", "   White to move")
+        );
+    }
+
+    #[test]
+    fn bfen_round_trip() {
+        let bfen = format!(
+            "{} | {}",
+            "4k3/7P/8/q7/8/8/PPPPPPP1/RNBQKBNR/ w KQ - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ppppppppnnbbrr w KQkq - 0 1",
+        );
+        let mut game = BughouseGame::from_str(&bfen).unwrap();
+        assert_eq!(game.to_bfen(), bfen);
+        for mv in &["h7h8q", "e8e7", "h8h5", "a5h5"] {
+            game.make_move(BoardID::A, &get_mv(mv)).unwrap();
+            let snapshot = BughouseGame::from_str(&game.to_bfen()).unwrap();
+            assert_eq!(snapshot, game);
+            let suffixed = game.to_bfen_as(HoldingsFormat::Suffix);
+            assert_eq!(BughouseGame::from_str(&suffixed).unwrap(), game);
+        }
+        assert_eq!(
+            game.to_bfen(),
+            format!(
+                "{} | {}",
+                "8/4k3/8/7q/8/8/PPPPPPP1/RNBQKBNR/ w KQ - 0 3",
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/Pppppppppnnbbrr w KQkq - 0 1",
+            )
+        );
+    }
+
+    #[test]
+    fn chess960_games() {
+        let seeded = BughouseGame::chess960_seeded(42, false);
+        assert_eq!(seeded, BughouseGame::chess960_seeded(42, false));
+        assert_ne!(
+            seeded.get_board(BoardID::A),
+            seeded.get_board(BoardID::B)
+        );
+        assert!(BughouseGame::chess960(0, 960).is_none());
+
+        // Castling onto your own rook captures nothing for your partner
+        let a = "rk2r3/pppppppp/8/8/8/8/PPPPPPPP/RK2R3/ w KQkq - 0 1";
+        let b = BughouseBoard::default().to_bfen();
+        let bfen = format!("{} | {}", a, b);
+        let mut game = BughouseGame::from_str(&bfen).unwrap();
+        let outcome = game.make_move(BoardID::A, &get_mv("b1e1")).unwrap();
+        assert_eq!(outcome.get_captured(), None);
+        assert_eq!(outcome.get_transferred(), None);
+        assert_eq!(
+            game.to_bfen(),
+            format!(
+                "rk2r3/pppppppp/8/8/8/8/PPPPPPPP/R4RK1/ b kq - 1 1 | {}",
+                b
+            )
+        );
+    }
+
+    #[test]
+    fn board_ids_beyond_b() {
+        let board_c = MultiBoardID::new(2).unwrap();
+        assert_eq!(board_c.to_string(), "C");
+        assert_eq!(format!("{:?}", MultiBoardID::A), "A");
+        assert_eq!(MultiBoardID::new(1), Some(MultiBoardID::B));
+        assert_eq!(MultiBoardID::new(MAX_BOARDS), None);
+        assert_eq!(BoardID::B.to_multi(), MultiBoardID::B);
+
+        let white_c = Seat::new(board_c, Color::White);
+        assert_eq!(white_c.to_string(), "WhiteC");
+        assert_eq!(white_c.team(), Team::One);
+        for seat in ALL_SEATS.iter() {
+            let multi = seat.to_multi();
+            assert_eq!(multi.to_string(), seat.to_string());
+            assert_eq!(multi.team(), seat.team());
+            assert_eq!(multi.to_index(), seat.to_index());
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use crate::bughouse_board::*;
 use crate::error::*;
-use chess::{ChessMove, /*Error,*/ Piece, Square};
+use chess::{ChessMove, /*Error,*/ Color, File, MoveGen, Piece, Square};
 use std::fmt;
 use std::str::FromStr;
 
@@ -48,10 +48,8 @@ impl BughouseMove {
 
     #[inline]
     pub fn to_chess_move(&self) -> Option<ChessMove> {
-        match self.source {
-            None => None,
-            Some(src) => Some(ChessMove::new(src, self.dest, self.piece)),
-        }
+        self.source
+            .map(|src| ChessMove::new(src, self.dest, self.piece))
     }
 
     /// Convert a "BAN", Bughouse-extended (Standard) Algebraic Notation move
@@ -70,15 +68,40 @@ impl BughouseMove {
         board: &BughouseBoard,
         move_text: &str,
     ) -> Result<BughouseMove, Error> {
-        if let Some(mv) = BughouseMove::from_drop_str(move_text) {
-            return if board.is_legal(&mv) {
-                Ok(mv)
-            } else {
-                Err(Error::IllegalMove(move_text.to_string()))
+        // Check(mate) and annotation suffixes carry no information we need
+        let san = move_text
+            .trim_end_matches(&['+', '#', '!', '?'][..])
+            .replace('=', "");
+        if let Some(mv) = BughouseMove::from_drop_str(&san) {
+            return board
+                .check_move(&mv)
+                .map(|_| mv)
+                .map_err(|reason| {
+                    Error::IllegalMove(move_text.to_string(), reason)
+                });
+        }
+
+        // chess::Board only knows classical castling
+        let kingside = match san.as_str() {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+        if let (Some(kingside), Some(rooks)) =
+            (kingside, board.get_castling_rooks())
+        {
+            let color = board.side_to_move();
+            if let Some(file) = rooks.get(color, kingside) {
+                let king = board.get_board().king_square(color);
+                let rook = Square::make_square(color.to_my_backrank(), file);
+                let mv = BughouseMove::new(Some(king), rook, None);
+                return board.check_move(&mv).map(|_| mv).map_err(|reason| {
+                    Error::IllegalMove(move_text.to_string(), reason)
+                });
             }
         }
 
-        let mv = ChessMove::from_san(board.get_board(), move_text)?;
+        let mv = ChessMove::from_san(board.get_board(), &san)?;
         Ok(BughouseMove::new(
                 Some(mv.get_source()),
                 mv.get_dest(),
@@ -86,6 +109,113 @@ impl BughouseMove {
                 ))
     }
 
+    /// Convert a legal move on `board` into "BAN", (Standard) Algebraic
+    /// Notation extended with drops. The inverse of `from_ban`.
+    ///
+    /// Mate (`#`) follows bughouse semantics (see `BughouseBoard::is_mated`):
+    /// a checkmate that a drop could block is only check (`+`).
+    ///
+    /// ```
+    /// use bughouse::{BughouseBoard, BughouseMove};
+    /// use std::str::FromStr;
+    ///
+    /// let board = BughouseBoard::from_str(
+    ///     "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR/N w KQkq - 0 2",
+    /// ).unwrap();
+    /// let mv = BughouseMove::from_str("N@d6").unwrap();
+    /// assert_eq!(mv.to_ban(&board), "N@d6+");
+    /// ```
+    pub fn to_ban(&self, board: &BughouseBoard) -> String {
+        let chess_board = board.get_board();
+        let mut ban = match self.source {
+            None => format!(
+                "{}@{}",
+                self.piece
+                    .map_or(String::new(), |p| p.to_string(Color::White)),
+                self.dest
+            ),
+            Some(src) => {
+                let piece = chess_board.piece_on(src).unwrap_or(Piece::Pawn);
+                let file_diff = src.get_file().to_index() as i32
+                    - self.dest.get_file().to_index() as i32;
+                let castle = match board.castle_960(self) {
+                    Some((king, rook)) => {
+                        Some(rook.get_file() > king.get_file())
+                    }
+                    None if piece == Piece::King && file_diff.abs() == 2 => {
+                        Some(self.dest.get_file() == File::G)
+                    }
+                    None => None,
+                };
+                if let Some(kingside) = castle {
+                    if kingside {
+                        "O-O"
+                    } else {
+                        "O-O-O"
+                    }
+                    .to_string()
+                } else {
+                    // Pawns changing files are captures, even en passant
+                    let is_capture = chess_board.piece_on(self.dest).is_some()
+                        || (piece == Piece::Pawn && file_diff != 0);
+                    let mut ban = String::new();
+                    if piece == Piece::Pawn {
+                        if is_capture {
+                            ban.push_str(&src.to_string()[..1]);
+                        }
+                    } else {
+                        ban.push_str(&piece.to_string(Color::White));
+                        ban.push_str(&self.disambiguation(board));
+                    }
+                    if is_capture {
+                        ban.push('x');
+                    }
+                    ban.push_str(&self.dest.to_string());
+                    if let Some(promo) = self.piece {
+                        ban.push('=');
+                        ban.push_str(&promo.to_string(Color::White));
+                    }
+                    ban
+                }
+            }
+        };
+        let mut next = board.clone();
+        if next.make_move(self).is_ok() {
+            if next.is_mated() {
+                ban.push('#');
+            } else if next.in_check() {
+                ban.push('+');
+            }
+        }
+        ban
+    }
+
+    // The minimal source file and/or rank needed to tell this move apart from
+    // moves of other pieces of the same type to the same square.
+    fn disambiguation(&self, board: &BughouseBoard) -> String {
+        let chess_board = board.get_board();
+        let src = self.source.unwrap();
+        let piece = chess_board.piece_on(src);
+        let others: Vec<Square> = MoveGen::new_legal(chess_board)
+            .filter(|other| {
+                other.get_dest() == self.dest
+                    && other.get_source() != src
+                    && chess_board.piece_on(other.get_source()) == piece
+            })
+            .map(|other| other.get_source())
+            .collect();
+        let square = src.to_string();
+        if others.is_empty() {
+            String::new()
+        } else if others.iter().all(|sq| sq.get_file() != src.get_file()) {
+            square[..1].to_string()
+        } else if others.iter().all(|sq| sq.get_rank() != src.get_rank()) {
+            square[1..].to_string()
+        } else {
+            square
+        }
+    }
+
     /// Convert drop algebraic notation to BughouseMove
     /// e.g. drops: "p@f7"
     pub fn from_drop_str(drop_str: &str) -> Option<Self> {
@@ -149,12 +279,12 @@ impl FromStr for BughouseMove {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Ok(mv) = ChessMove::from_str(s) {
             return Ok(BughouseMove::from_chess_move(&mv));
-        } else if let Some(mv) = BughouseMove::from_drop_str(&s) {
+        } else if let Some(mv) = BughouseMove::from_drop_str(s) {
             return Ok(mv);
         }
         // Allow something like "e5n" or "bf7"?
         // This author prefers unambiguious BPGN... so
-        return Err(Error::MoveParseError(s.to_string()));
+        Err(Error::MoveParseError(s.to_string()))
     }
 }
 
@@ -166,6 +296,7 @@ pub fn get_mv(mv_str: &str) -> BughouseMove {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::bughouse_movegen::BughouseMoveGen;
     use chess::Board;
 
     #[test]
@@ -175,6 +306,87 @@ mod test {
         assert!(BughouseMove::from_str("h@e5").is_err());
     }
 
+    #[test]
+    pub fn test_from_ban_suffixes() {
+        let board = BughouseBoard::from_str(
+            "rn1qkbnr/pP2pppp/2b5/8/8/8/PPPP1PPP/RNBQKBNR/N w KQkq - 0 1",
+        )
+        .unwrap();
+        let promo = BughouseMove::new(
+            Some(Square::B7),
+            Square::A8,
+            Some(Piece::Queen),
+        );
+        assert_eq!(BughouseMove::from_ban(&board, "bxa8=Q").unwrap(), promo);
+        assert_eq!(BughouseMove::from_ban(&board, "bxa8Q").unwrap(), promo);
+        assert_eq!(
+            BughouseMove::from_ban(&board, "N@d6+").unwrap(),
+            BughouseMove::from_str("N@d6").unwrap()
+        );
+        assert!(BughouseMove::from_ban(&board, "B@d6").is_err());
+    }
+
+    #[test]
+    pub fn test_to_ban() {
+        let board = BughouseBoard::from_str(
+            "4k3/8/8/8/8/2N3N1/8/R3K2R/Q w KQ - 0 1",
+        )
+        .unwrap();
+        for (mv, ban) in &[
+            ("c3e4", "Nce4"),
+            ("g3e4", "Nge4"),
+            ("a1d1", "Rd1"),
+            ("e1g1", "O-O"),
+            ("e1c1", "O-O-O"),
+            ("Q@e7", "Q@e7+"),
+            ("Q@b2", "Q@b2"),
+        ] {
+            assert_eq!(get_mv(mv).to_ban(&board), *ban);
+        }
+
+        let board = BughouseBoard::from_str("k7/8/8/1N3N2/8/1N6/8/K7/ w - - 0 1")
+            .unwrap();
+        assert_eq!(get_mv("b5d4").to_ban(&board), "Nb5d4");
+        assert_eq!(get_mv("b3d4").to_ban(&board), "N3d4");
+        assert_eq!(get_mv("f5d4").to_ban(&board), "Nfd4");
+
+        let board = BughouseBoard::from_str(
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR/ w KQkq f6 0 3",
+        )
+        .unwrap();
+        assert_eq!(get_mv("e5f6").to_ban(&board), "exf6");
+        assert_eq!(get_mv("e5e6").to_ban(&board), "e6");
+    }
+
+    #[test]
+    pub fn test_to_ban_promotion_and_mate() {
+        let board = BughouseBoard::from_str("k7/8/8/8/8/8/6p1/K7/ b - - 0 1")
+            .unwrap();
+        assert_eq!(get_mv("g2g1q").to_ban(&board), "g1=Q+");
+        assert_eq!(get_mv("g2g1n").to_ban(&board), "g1=N");
+
+        // Chess checkmate, but a drop on b8 could still block it
+        let board = BughouseBoard::from_str("k7/pp6/8/8/8/8/8/K6R/ w - - 0 1")
+            .unwrap();
+        assert_eq!(get_mv("h1h8").to_ban(&board), "Rh8+");
+        // Smothered mate can't be blocked
+        let board = BughouseBoard::from_str("kr6/pp6/8/1N6/8/8/8/K7/ w - - 0 1")
+            .unwrap();
+        assert_eq!(get_mv("b5c7").to_ban(&board), "Nc7#");
+    }
+
+    #[test]
+    pub fn test_ban_round_trip() {
+        let board = BughouseBoard::from_str(
+            "r2k1r2/pbppNppp/1p2p1nb/1P5N/3N4/4Pn1q/PPP1QP1P/2KR2R1/BrpBBqppN w - - 45 56",
+        )
+        .unwrap();
+        for mv in BughouseMoveGen::new_legal(&board) {
+            let ban = mv.to_ban(&board);
+            assert_eq!(BughouseMove::from_ban(&board, &ban).unwrap(), mv, "{}", ban);
+        }
+    }
+
     #[test]
     pub fn test_promo() {
         let fen = "rn1qkbnr/pP2pppp/2b5/8/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1";
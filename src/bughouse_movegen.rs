@@ -0,0 +1,177 @@
+use crate::bughouse_board::BughouseBoard;
+use crate::bughouse_move::BughouseMove;
+use crate::holdings::NUM_HELD_PIECE_TYPES;
+use chess::{BitBoard, MoveGen, Piece, ALL_PIECES, EMPTY};
+
+/// An iterator over every legal `BughouseMove` on a `BughouseBoard`.
+///
+/// Regular board moves (from `chess::MoveGen`) are yielded first, then any
+/// Chess960 castles (see `BughouseBoard::get_castling_rooks`), followed by
+/// every legal drop of a held piece, ordered by piece (P, N, B, R, Q) then
+/// square.
+///
+/// ```
+/// use bughouse::{BughouseBoard, BughouseMoveGen};
+///
+/// let board = BughouseBoard::default();
+/// assert_eq!(BughouseMoveGen::new_legal(&board).len(), 20);
+/// ```
+pub struct BughouseMoveGen {
+    board_moves: MoveGen,
+    castles: std::vec::IntoIter<BughouseMove>,
+    // Drop squares for each held piece type (EMPTY if not held)
+    drops: [BitBoard; NUM_HELD_PIECE_TYPES],
+    piece_idx: usize,
+}
+
+impl BughouseMoveGen {
+    /// Create an iterator over all legal moves and drops for the side to move.
+    pub fn new_legal(board: &BughouseBoard) -> Self {
+        let color = board.side_to_move();
+        let holdings = board.get_holdings();
+        let rules = board.get_rules();
+        let drop_squares = board.drop_squares();
+        let mut drops = [EMPTY; NUM_HELD_PIECE_TYPES];
+        for (idx, squares) in drops.iter_mut().enumerate() {
+            let piece = ALL_PIECES[idx];
+            if !holdings.has_piece(color, piece) {
+                continue;
+            }
+            *squares = if piece == Piece::Pawn {
+                drop_squares & rules.get_pawn_drop_squares(color)
+            } else {
+                drop_squares
+            };
+            if !rules.allows_drop_mates() {
+                for sq in *squares {
+                    if board.is_drop_mate(piece, sq) {
+                        *squares ^= BitBoard::from_square(sq);
+                    }
+                }
+            }
+        }
+        BughouseMoveGen {
+            board_moves: MoveGen::new_legal(board.get_board()),
+            castles: board.castles_960().into_iter(),
+            drops,
+            piece_idx: 0,
+        }
+    }
+
+    fn drops_remaining(&self) -> usize {
+        self.drops[self.piece_idx..]
+            .iter()
+            .map(|bb| bb.popcnt() as usize)
+            .sum()
+    }
+}
+
+impl Iterator for BughouseMoveGen {
+    type Item = BughouseMove;
+
+    fn next(&mut self) -> Option<BughouseMove> {
+        if let Some(mv) = self.board_moves.next() {
+            return Some(BughouseMove::from_chess_move(&mv));
+        }
+        if let Some(mv) = self.castles.next() {
+            return Some(mv);
+        }
+        while self.piece_idx < NUM_HELD_PIECE_TYPES {
+            let squares = &mut self.drops[self.piece_idx];
+            if let Some(sq) = squares.next() {
+                let piece = ALL_PIECES[self.piece_idx];
+                return Some(BughouseMove::new(None, sq, Some(piece)));
+            }
+            self.piece_idx += 1;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for BughouseMoveGen {
+    fn len(&self) -> usize {
+        self.board_moves.len() + self.castles.len() + self.drops_remaining()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bughouse_move::get_mv;
+    use chess::Square;
+    use std::str::FromStr;
+
+    fn gen_moves(bfen: &str) -> Vec<BughouseMove> {
+        let board = BughouseBoard::from_str(bfen).unwrap();
+        BughouseMoveGen::new_legal(&board).collect()
+    }
+
+    #[test]
+    fn initial_position() {
+        let board = BughouseBoard::default();
+        let moves: Vec<BughouseMove> =
+            BughouseMoveGen::new_legal(&board).collect();
+        assert_eq!(moves.len(), 20);
+        assert!(moves.iter().all(|mv| mv.get_source().is_some()));
+    }
+
+    #[test]
+    fn drops_on_every_empty_square() {
+        // A knight can go on any of the 32 empty squares
+        let moves = gen_moves(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/N w KQkq - 0 1",
+        );
+        assert_eq!(moves.len(), 20 + 32);
+        assert!(moves.contains(&get_mv("N@e4")));
+    }
+
+    #[test]
+    fn no_pawn_drops_on_back_ranks() {
+        let moves = gen_moves("k7/8/8/8/8/8/8/7K/P w - - 0 1");
+        let drops: Vec<&BughouseMove> = moves
+            .iter()
+            .filter(|mv| mv.get_source().is_none())
+            .collect();
+        // 62 empty squares, 14 of them on ranks 1 and 8
+        assert_eq!(drops.len(), 48);
+        assert!(!moves.contains(&get_mv("P@h8")));
+        assert!(moves.contains(&get_mv("P@h7")));
+    }
+
+    #[test]
+    fn drops_must_block_check() {
+        let moves = gen_moves("3k4/8/8/8/8/8/8/K6q/NP w - - 45 56");
+        let mut drops: Vec<BughouseMove> = moves
+            .into_iter()
+            .filter(|mv| mv.get_source().is_none())
+            .collect();
+        drops.sort_by_key(|mv| (mv.get_piece(), mv.get_dest()));
+        // Pawns can't go on the first rank, so only knight interpositions
+        assert_eq!(drops.len(), 6);
+        assert!(drops.iter().all(|mv| mv.get_piece() == Some(Piece::Knight)));
+        assert_eq!(drops[0].get_dest(), Square::B1);
+
+        // Knight checks can't be blocked
+        assert!(gen_moves("3k4/8/8/8/8/8/2n/K7/N w - - 45 56")
+            .iter()
+            .all(|mv| mv.get_source().is_some()));
+    }
+
+    #[test]
+    fn generated_moves_are_legal() {
+        let bfen = "r2k1r2/pbppNppp/1p2p1nb/1P5N/3N4/4Pn1q/PPP1QP1P/2KR2R1/BrpBBqppN w - - 45 56";
+        let board = BughouseBoard::from_str(bfen).unwrap();
+        let gen = BughouseMoveGen::new_legal(&board);
+        let expected = gen.len();
+        let moves: Vec<BughouseMove> = gen.collect();
+        assert_eq!(moves.len(), expected);
+        for mv in &moves {
+            assert!(board.is_legal(mv), "{} should be legal", mv);
+        }
+    }
+}
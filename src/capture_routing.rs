@@ -0,0 +1,109 @@
+use crate::bughouse_game::{MultiBoardID, MultiSeat, Seat};
+use crate::error::Error;
+use chess::{Color, ALL_COLORS};
+
+/// Where each seat's captures go in a game of any number of boards: the
+/// seat, usually a teammate on another board, whose holdings receive them.
+///
+/// ```
+/// use bughouse::{CaptureRouting, MultiBoardID, Seat};
+/// use chess::Color;
+///
+/// // Three boards, with board C passing to board A
+/// let routing = CaptureRouting::round_robin(3);
+/// let white_c = Seat::new(MultiBoardID::new(2).unwrap(), Color::White);
+/// assert_eq!(
+///     routing.get_receiver(white_c),
+///     Seat::new(MultiBoardID::A, Color::White)
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct CaptureRouting {
+    // The receiving seat, indexed by the capturing seat
+    routes: Vec<MultiSeat>,
+}
+
+impl CaptureRouting {
+    /// Each seat passes to its teammate on the next board, and the last
+    /// board to the first.  On two boards, that's regular bughouse; on one,
+    /// it's crazyhouse.  Panics beyond `MAX_BOARDS` boards.
+    pub fn round_robin(num_boards: usize) -> Self {
+        let mut routes = Vec::with_capacity(num_boards * 2);
+        for idx in 0..num_boards {
+            let next = MultiBoardID::new((idx + 1) % num_boards).unwrap();
+            for color in ALL_COLORS.iter() {
+                let seat = Seat::new(MultiBoardID::new(idx).unwrap(), *color);
+                let white = Seat::new(next, Color::White);
+                routes.push(if white.team() == seat.team() {
+                    white
+                } else {
+                    white.opponent()
+                });
+            }
+        }
+        CaptureRouting { routes }
+    }
+
+    /// Send `from`'s captures to `to` instead.  Both must be on one of the
+    /// routed boards.
+    pub fn with_route(
+        mut self,
+        from: MultiSeat,
+        to: MultiSeat,
+    ) -> Result<Self, Error> {
+        for seat in [from, to].iter() {
+            if seat.get_board().to_index() >= self.num_boards() {
+                return Err(Error::UnknownBoard(seat.get_board()));
+            }
+        }
+        self.routes[from.to_index()] = to;
+        Ok(self)
+    }
+
+    #[inline]
+    pub fn num_boards(&self) -> usize {
+        self.routes.len() / 2
+    }
+
+    /// The seat receiving what `capturer` captures.
+    #[inline]
+    pub fn get_receiver(&self, capturer: MultiSeat) -> MultiSeat {
+        self.routes[capturer.to_index()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bughouse_game::ALL_SEATS;
+
+    #[test]
+    fn round_robin() {
+        let bughouse = CaptureRouting::round_robin(2);
+        for seat in ALL_SEATS.iter() {
+            let receiver = bughouse.get_receiver(seat.to_multi());
+            assert_eq!(receiver, seat.partner().to_multi());
+        }
+        let crazyhouse = CaptureRouting::round_robin(1);
+        let white = Seat::new(MultiBoardID::A, Color::White);
+        assert_eq!(crazyhouse.get_receiver(white), white);
+
+        // Teams alternate colors from board to board
+        let routing = CaptureRouting::round_robin(3);
+        assert_eq!(routing.num_boards(), 3);
+        let board_c = MultiBoardID::new(2).unwrap();
+        let black_b = Seat::new(MultiBoardID::B, Color::Black);
+        assert_eq!(
+            routing.get_receiver(black_b),
+            Seat::new(board_c, Color::White)
+        );
+        let black_c = Seat::new(board_c, Color::Black);
+        assert_eq!(routing.get_receiver(black_c), white.opponent());
+
+        let routing = routing.with_route(white, black_b).unwrap();
+        assert_eq!(routing.get_receiver(white), black_b);
+        let white_d = Seat::new(MultiBoardID::new(3).unwrap(), Color::White);
+        assert!(routing.clone().with_route(white_d, white).is_err());
+        assert!(routing.with_route(white, white_d).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use crate::zobrist::castling_key;
+use chess::{CastleRights, Color, File, Piece, Square, ALL_COLORS, NUM_COLORS};
+
+/// The number of Chess960 (Fischer random) starting positions.
+pub const NUM_CHESS960_POSITIONS: u16 = 960;
+
+/// The Chess960 number of the classical starting position.
+pub const CLASSICAL_CHESS960_ID: u16 = 518;
+
+// The 10 ways to place two knights on the 5 squares left after the bishops
+// and queen, in Scharnagl's order
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// White's back rank (a-file first) in Chess960 starting position `id`,
+/// numbered 0-959 as Scharnagl does.  Black mirrors it.  `None` if `id` is
+/// out of range.
+///
+/// ```
+/// use bughouse::{chess960_back_rank, CLASSICAL_CHESS960_ID};
+/// use chess::Piece;
+///
+/// let classical = chess960_back_rank(CLASSICAL_CHESS960_ID).unwrap();
+/// assert_eq!(classical[4], Piece::King);
+/// assert_eq!(chess960_back_rank(960), None);
+/// ```
+pub fn chess960_back_rank(id: u16) -> Option<[Piece; 8]> {
+    if id >= NUM_CHESS960_POSITIONS {
+        return None;
+    }
+    let mut rank = [None; 8];
+    let mut n = id as usize;
+    // A bishop on each color, then the queen and knights on the nth empty
+    // square, leaving rook, king, rook (in that order) for the rest
+    rank[n % 4 * 2 + 1] = Some(Piece::Bishop);
+    n /= 4;
+    rank[n % 4 * 2] = Some(Piece::Bishop);
+    n /= 4;
+    let empty = |rank: &[Option<Piece>; 8]| -> Vec<usize> {
+        (0..8).filter(|idx| rank[*idx].is_none()).collect()
+    };
+    rank[empty(&rank)[n % 6]] = Some(Piece::Queen);
+    n /= 6;
+    let (first, second) = KNIGHTS[n];
+    let squares = empty(&rank);
+    rank[squares[first]] = Some(Piece::Knight);
+    rank[squares[second]] = Some(Piece::Knight);
+    for (idx, piece) in empty(&rank)
+        .into_iter()
+        .zip([Piece::Rook, Piece::King, Piece::Rook].iter())
+    {
+        rank[idx] = Some(*piece);
+    }
+    let mut pieces = [Piece::Pawn; 8];
+    for (piece, placed) in pieces.iter_mut().zip(rank.iter()) {
+        *piece = placed.unwrap();
+    }
+    Some(pieces)
+}
+
+/// How castling rights are written in (B)FEN.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum CastlingNotation {
+    /// X-FEN: `KQkq` for the outermost rook on each side of the king, and
+    /// the rook's file otherwise.  The same as FEN in classical positions.
+    XFen,
+    /// Shredder-FEN: always the rook's file, e.g. `HAha`.
+    Shredder,
+}
+
+/// The rooks each side may still castle with, for Chess960 positions whose
+/// castling `chess::Board` can't represent.  In Chess960 castling, the king
+/// ends up on the g-file (c-file) and the rook on the f-file (d-file) as
+/// usual, wherever they started.
+///
+/// Rights are only ever lost: moving the king or the rook, or having the
+/// rook captured, gives them up for good, and a rook dropped on the square
+/// doesn't bring them back.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct CastlingRooks {
+    // The rook's file, by color and then kingside (0) or queenside (1)
+    files: [[Option<File>; 2]; NUM_COLORS],
+}
+
+impl CastlingRooks {
+    #[inline]
+    pub fn new() -> Self {
+        CastlingRooks::default()
+    }
+
+    /// The file of the rook `color` may castle with on the kingside (toward
+    /// the h-file) or the queenside.
+    #[inline]
+    pub fn get(&self, color: Color, kingside: bool) -> Option<File> {
+        self.files[color.to_index()][!kingside as usize]
+    }
+
+    pub(crate) fn set(&mut self, color: Color, kingside: bool, file: File) {
+        self.files[color.to_index()][!kingside as usize] = Some(file);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.iter().flatten().all(|file| file.is_none())
+    }
+
+    /// `color` moved their king.
+    pub(crate) fn clear_color(&mut self, color: Color) {
+        self.files[color.to_index()] = [None, None];
+    }
+
+    /// A piece moved from or to `sq`, so the rook that was there (if any)
+    /// can't castle anymore.
+    pub(crate) fn clear_square(&mut self, sq: Square) {
+        for color in ALL_COLORS.iter() {
+            if sq.get_rank() != color.to_my_backrank() {
+                continue;
+            }
+            for file in self.files[color.to_index()].iter_mut() {
+                if *file == Some(sq.get_file()) {
+                    *file = None;
+                }
+            }
+        }
+    }
+
+    /// `color`'s rights as `chess::CastleRights`, if they are classical ones:
+    /// the king on the e-file and rooks on the a- and h-files.
+    pub(crate) fn classical_rights(
+        &self,
+        color: Color,
+        king: Square,
+    ) -> Option<CastleRights> {
+        let [kingside, queenside] = self.files[color.to_index()];
+        let rights = match (kingside, queenside) {
+            (None, None) => return Some(CastleRights::NoRights),
+            (Some(File::H), None) => CastleRights::KingSide,
+            (None, Some(File::A)) => CastleRights::QueenSide,
+            (Some(File::H), Some(File::A)) => CastleRights::Both,
+            _ => return None,
+        };
+        let home = Square::make_square(color.to_my_backrank(), File::E);
+        if king == home {
+            Some(rights)
+        } else {
+            None
+        }
+    }
+
+    /// Zobrist hash of the rights (see `BughouseBoard::get_hash`).
+    pub fn get_hash(&self) -> u64 {
+        let mut hash = 0;
+        for color in ALL_COLORS.iter() {
+            for file in self.files[color.to_index()].iter().flatten() {
+                hash ^= castling_key(*color, *file);
+            }
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn to_string(id: u16) -> String {
+        chess960_back_rank(id)
+            .unwrap()
+            .iter()
+            .map(|piece| piece.to_string(Color::White))
+            .collect()
+    }
+
+    #[test]
+    fn scharnagl_numbering() {
+        assert_eq!(to_string(0), "BBQNNRKR");
+        assert_eq!(to_string(CLASSICAL_CHESS960_ID), "RNBQKBNR");
+        assert_eq!(to_string(959), "RKRNNQBB");
+        for id in 0..NUM_CHESS960_POSITIONS {
+            let rank = to_string(id);
+            let king = rank.find('K').unwrap();
+            assert!(rank.find('R').unwrap() < king, "{}", rank);
+            assert!(rank.rfind('R').unwrap() > king, "{}", rank);
+            let bishops: Vec<usize> =
+                rank.match_indices('B').map(|(idx, _)| idx % 2).collect();
+            assert_eq!(bishops.len(), 2);
+            assert_ne!(bishops[0], bishops[1], "{}", rank);
+        }
+    }
+
+    #[test]
+    fn castling_rooks() {
+        let mut rooks = CastlingRooks::new();
+        assert!(rooks.is_empty());
+        rooks.set(Color::White, true, File::H);
+        rooks.set(Color::White, false, File::B);
+        rooks.set(Color::Black, true, File::H);
+        assert_eq!(rooks.classical_rights(Color::White, Square::E1), None);
+        assert_eq!(
+            rooks.classical_rights(Color::Black, Square::E8),
+            Some(CastleRights::KingSide)
+        );
+        assert_eq!(rooks.classical_rights(Color::Black, Square::F8), None);
+
+        // Only the back rank of the rook's owner matters
+        rooks.clear_square(Square::B8);
+        assert_eq!(rooks.get(Color::White, false), Some(File::B));
+        rooks.clear_square(Square::B1);
+        assert_eq!(rooks.get(Color::White, false), None);
+        assert_eq!(
+            rooks.classical_rights(Color::White, Square::E1),
+            Some(CastleRights::KingSide)
+        );
+        rooks.clear_color(Color::White);
+        rooks.clear_color(Color::Black);
+        assert!(rooks.is_empty());
+        assert_eq!(rooks.get_hash(), 0);
+    }
+}
@@ -0,0 +1,407 @@
+use crate::bughouse_game::{BoardID, Seat, ALL_SEATS};
+use crate::error::*;
+use chess::{Color, NUM_COLORS};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A source of monotonic time, injectable so clocks are testable without
+/// waiting on the wall clock.
+pub trait TimeSource: Send + Sync {
+    /// Time elapsed since some fixed (arbitrary) starting point.
+    fn now(&self) -> Duration;
+}
+
+/// Real, monotonic time.
+#[derive(Clone, Copy, Debug)]
+pub struct WallTime {
+    start: Instant,
+}
+
+impl WallTime {
+    pub fn new() -> Self {
+        WallTime {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for WallTime {
+    fn default() -> Self {
+        WallTime::new()
+    }
+}
+
+impl TimeSource for WallTime {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Time that only moves when told to.  Clones share the same time.
+#[derive(Clone, Debug, Default)]
+pub struct ManualTime {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualTime {
+    pub fn new() -> Self {
+        ManualTime::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Base time per player, a Fischer increment added after each move, and a
+/// simple (US) delay that elapses before the clock starts counting down.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct TimeControl {
+    base: Duration,
+    increment: Duration,
+    delay: Duration,
+}
+
+impl TimeControl {
+    pub fn new(base: Duration, increment: Duration) -> Self {
+        TimeControl {
+            base,
+            increment,
+            delay: Duration::default(),
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    #[inline]
+    pub fn get_base(&self) -> Duration {
+        self.base
+    }
+
+    #[inline]
+    pub fn get_increment(&self) -> Duration {
+        self.increment
+    }
+
+    #[inline]
+    pub fn get_delay(&self) -> Duration {
+        self.delay
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = Error;
+
+    /// Parse PGN style "<base seconds>+<increment seconds>", e.g. "180+2"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || Error::TimeControlParseError(s.to_string());
+        let (base, increment) = s.trim().split_once('+').unwrap_or((s, "0"));
+        let base: u64 = base.trim().parse().map_err(|_| err())?;
+        let increment: u64 = increment.trim().parse().map_err(|_| err())?;
+        Ok(TimeControl::new(
+            Duration::from_secs(base),
+            Duration::from_secs(increment),
+        ))
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}+{}", self.base.as_secs(), self.increment.as_secs())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct BoardClocks {
+    remaining: [Duration; NUM_COLORS],
+    // Which clock is running and when it was (re)started
+    running: Option<(Color, Duration)>,
+    // When each clock ran out, once it's been charged for running out
+    ran_out_at: [Option<Duration>; NUM_COLORS],
+}
+
+/// The four clocks of a bughouse game, one per seat.
+#[derive(Clone)]
+pub struct Clocks {
+    time_control: TimeControl,
+    boards: [BoardClocks; 2],
+    source: Arc<dyn TimeSource>,
+}
+
+impl Clocks {
+    pub fn new(time_control: TimeControl, source: Arc<dyn TimeSource>) -> Self {
+        let board = BoardClocks {
+            remaining: [time_control.base; NUM_COLORS],
+            running: None,
+            ran_out_at: [None; NUM_COLORS],
+        };
+        Clocks {
+            time_control,
+            boards: [board; 2],
+            source,
+        }
+    }
+
+    #[inline]
+    pub fn get_time_control(&self) -> TimeControl {
+        self.time_control
+    }
+
+    /// Start White's clock on both boards.
+    pub fn start(&mut self) {
+        let now = self.source.now();
+        for board in self.boards.iter_mut() {
+            if board.running.is_none() {
+                board.running = Some((Color::White, now));
+            }
+        }
+    }
+
+    /// Stop every clock, charging any running clock for its time.
+    pub fn stop(&mut self) {
+        let now = self.source.now();
+        for board in self.boards.iter_mut() {
+            Self::charge(&self.time_control, board, now);
+            board.running = None;
+        }
+    }
+
+    /// The color whose clock is running on `board`, if any.
+    pub fn running(&self, board: BoardID) -> Option<Color> {
+        self.boards[board.to_index()]
+            .running
+            .map(|(color, _)| color)
+    }
+
+    /// Time left for `seat`, as of now.
+    pub fn remaining(&self, seat: Seat) -> Duration {
+        let clocks = &self.boards[seat.get_board().to_index()];
+        let remaining = clocks.remaining[seat.get_color().to_index()];
+        match clocks.running {
+            Some((running, since)) if running == seat.get_color() => {
+                let elapsed =
+                    elapsed(&self.time_control, since, self.source.now());
+                remaining.checked_sub(elapsed).unwrap_or_default()
+            }
+            _ => remaining,
+        }
+    }
+
+    /// Whether `seat` has run out of time.
+    pub fn is_flagged(&self, seat: Seat) -> bool {
+        self.remaining(seat) == Duration::default()
+    }
+
+    /// The seat whose clock ran out first, if any has.
+    pub fn flagged(&self) -> Option<Seat> {
+        ALL_SEATS
+            .iter()
+            .filter_map(|seat| Some((self.ran_out_at(*seat)?, *seat)))
+            .min_by_key(|(at, _)| *at)
+            .map(|(_, seat)| seat)
+    }
+
+    // When `seat`'s clock ran out, if it has
+    fn ran_out_at(&self, seat: Seat) -> Option<Duration> {
+        if !self.is_flagged(seat) {
+            return None;
+        }
+        let clocks = self.boards.get(seat.get_board().to_index())?;
+        let color = seat.get_color();
+        Some(match (clocks.ran_out_at[color.to_index()], clocks.running) {
+            (Some(at), _) => at,
+            (None, Some((running, since))) if running == color => {
+                since
+                    + self.time_control.delay
+                    + clocks.remaining[color.to_index()]
+            }
+            // Never ran, so it was out of time from the start
+            _ => Duration::default(),
+        })
+    }
+
+    /// Stop `mover`'s clock, add the increment, and start their opponent's.
+    /// Called by `BughouseGame::make_move`.
+    pub fn record_move(&mut self, mover: Seat) {
+        let now = self.source.now();
+        let time_control = self.time_control;
+        let clocks = &mut self.boards[mover.get_board().to_index()];
+        Self::charge(&time_control, clocks, now);
+        clocks.remaining[mover.get_color().to_index()] +=
+            time_control.increment;
+        clocks.running = Some((!mover.get_color(), now));
+    }
+
+    // Deduct the running clock's time and restart it from `now`
+    fn charge(
+        time_control: &TimeControl,
+        clocks: &mut BoardClocks,
+        now: Duration,
+    ) {
+        if let Some((color, since)) = clocks.running {
+            let elapsed = elapsed(time_control, since, now);
+            let ran_out_at = &mut clocks.ran_out_at[color.to_index()];
+            let remaining = &mut clocks.remaining[color.to_index()];
+            if elapsed >= *remaining && ran_out_at.is_none() {
+                *ran_out_at = Some(since + time_control.delay + *remaining);
+            }
+            *remaining = remaining.checked_sub(elapsed).unwrap_or_default();
+            clocks.running = Some((color, now));
+        }
+    }
+}
+
+// Time to charge a clock running since `since`, after the delay
+fn elapsed(
+    time_control: &TimeControl,
+    since: Duration,
+    now: Duration,
+) -> Duration {
+    now.checked_sub(since)
+        .unwrap_or_default()
+        .checked_sub(time_control.delay)
+        .unwrap_or_default()
+}
+
+// The time source is deliberately left out: two sets of clocks are equal if
+// they're in the same state.
+impl PartialEq for Clocks {
+    fn eq(&self, other: &Self) -> bool {
+        self.time_control == other.time_control && self.boards == other.boards
+    }
+}
+
+impl Eq for Clocks {}
+
+impl fmt::Debug for Clocks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Clocks")
+            .field("time_control", &self.time_control)
+            .field("boards", &self.boards)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn parse_time_control() {
+        let tc = TimeControl::from_str("180+2").unwrap();
+        assert_eq!(tc, TimeControl::new(secs(180), secs(2)));
+        assert_eq!(tc.to_string(), "180+2");
+        assert_eq!(
+            TimeControl::from_str("120").unwrap().get_increment(),
+            secs(0)
+        );
+        assert!(TimeControl::from_str("2 minutes").is_err());
+    }
+
+    #[test]
+    fn moves_switch_clocks() {
+        let time = ManualTime::new();
+        let tc = TimeControl::new(secs(60), secs(2));
+        let mut clocks = Clocks::new(tc, Arc::new(time.clone()));
+        clocks.start();
+        assert_eq!(clocks.running(BoardID::A), Some(Color::White));
+        time.advance(secs(10));
+        assert_eq!(
+            clocks.remaining(Seat::new(BoardID::A, Color::White)),
+            secs(50)
+        );
+        assert_eq!(
+            clocks.remaining(Seat::new(BoardID::B, Color::White)),
+            secs(50)
+        );
+
+        clocks.record_move(Seat::new(BoardID::A, Color::White));
+        assert_eq!(clocks.running(BoardID::A), Some(Color::Black));
+        assert_eq!(
+            clocks.remaining(Seat::new(BoardID::A, Color::White)),
+            secs(52)
+        );
+        time.advance(secs(5));
+        assert_eq!(
+            clocks.remaining(Seat::new(BoardID::A, Color::White)),
+            secs(52)
+        );
+        assert_eq!(
+            clocks.remaining(Seat::new(BoardID::A, Color::Black)),
+            secs(55)
+        );
+        assert_eq!(
+            clocks.remaining(Seat::new(BoardID::B, Color::White)),
+            secs(45)
+        );
+
+        clocks.stop();
+        time.advance(secs(100));
+        assert_eq!(
+            clocks.remaining(Seat::new(BoardID::A, Color::Black)),
+            secs(55)
+        );
+        assert_eq!(clocks.flagged(), None);
+    }
+
+    #[test]
+    fn delay_and_flag() {
+        let time = ManualTime::new();
+        let tc = TimeControl::new(secs(10), secs(0)).with_delay(secs(3));
+        let mut clocks = Clocks::new(tc, Arc::new(time.clone()));
+        clocks.start();
+        time.advance(secs(2));
+        assert_eq!(
+            clocks.remaining(Seat::new(BoardID::A, Color::White)),
+            secs(10)
+        );
+        clocks.record_move(Seat::new(BoardID::A, Color::White));
+        assert_eq!(
+            clocks.remaining(Seat::new(BoardID::A, Color::White)),
+            secs(10)
+        );
+        time.advance(secs(20));
+        assert!(clocks.is_flagged(Seat::new(BoardID::A, Color::Black)));
+        // White on B, whose clock never stopped, ran out first
+        assert_eq!(clocks.flagged(), Some(Seat::new(BoardID::B, Color::White)));
+    }
+
+    #[test]
+    fn first_to_run_out_is_flagged() {
+        let time = ManualTime::new();
+        let tc = TimeControl::new(secs(60), secs(0));
+        let mut clocks = Clocks::new(tc, Arc::new(time.clone()));
+        clocks.start();
+        time.advance(secs(10));
+        clocks.record_move(Seat::new(BoardID::A, Color::White));
+        // White on B ran out at 60s, Black on A only at 70s
+        time.advance(secs(90));
+        let black_a = Seat::new(BoardID::A, Color::Black);
+        let white_b = Seat::new(BoardID::B, Color::White);
+        assert!(clocks.is_flagged(black_a));
+        assert!(clocks.is_flagged(white_b));
+        assert_eq!(clocks.flagged(), Some(white_b));
+        // Still so once the clocks have been charged
+        clocks.stop();
+        assert_eq!(clocks.flagged(), Some(white_b));
+    }
+}
@@ -0,0 +1,548 @@
+use crate::bpgn::{
+    format_header, parse_header, set_header, MAX_LINE_LEN, RESULTS,
+};
+use crate::bughouse_board::{BfenMode, BughouseBoard};
+use crate::bughouse_game::{BoardID, Seat};
+use crate::bughouse_move::BughouseMove;
+use crate::capture_routing::CaptureRouting;
+use crate::error::*;
+use crate::game_result::BughouseResult;
+use crate::holdings::HoldingsFormat;
+use crate::move_outcome::MoveOutcome;
+use crate::multi_bughouse_game::MultiBughouseGame;
+use crate::rules::Rules;
+use chess::Color;
+use std::fmt;
+use std::str::FromStr;
+
+/// A game of crazyhouse: a single board on which captured pieces go to the
+/// capturer's own hand, rather than to a partner.  Players and results are
+/// given as seats on board A.
+///
+/// Positions are read and written as Lichess style FEN (holdings in brackets)
+/// and games as PGN with a `Variant` header of `Crazyhouse`.
+///
+/// ```
+/// use bughouse::{BughouseMove, CrazyhouseGame};
+/// use chess::{Color, Piece};
+/// use std::str::FromStr;
+///
+/// let mut game = CrazyhouseGame::default();
+/// for mv in ["e2e4", "d7d5", "e4d5"].iter() {
+///     game.make_move(&BughouseMove::from_str(mv).unwrap()).unwrap();
+/// }
+/// let holdings = game.get_board().get_holdings();
+/// assert_eq!(holdings.count(Color::White, Piece::Pawn), 1);
+/// assert_eq!(
+///     game.to_fen(),
+///     "rnbqkbnr/ppp1pppp/8/3P4/8/8/PPPP1PPP/RNBQKBNR[P] b KQkq - 0 2"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct CrazyhouseGame {
+    initial: BughouseBoard,
+    // Board A, passing captures to the capturer
+    game: MultiBughouseGame<BoardID>,
+    headers: Vec<(String, String)>,
+}
+
+// The starting position and headers only say how the game got here
+impl PartialEq for CrazyhouseGame {
+    fn eq(&self, other: &Self) -> bool {
+        self.game == other.game
+    }
+}
+
+impl Eq for CrazyhouseGame {}
+
+impl Default for CrazyhouseGame {
+    #[inline]
+    fn default() -> Self {
+        CrazyhouseGame::new(BughouseBoard::default())
+    }
+}
+
+impl CrazyhouseGame {
+    pub fn new(board: BughouseBoard) -> Self {
+        let routing = CaptureRouting::round_robin(1);
+        CrazyhouseGame {
+            initial: board.clone(),
+            game: MultiBughouseGame::from_parts(vec![board], routing),
+            headers: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn get_board(&self) -> &BughouseBoard {
+        &self.game.get_boards()[0]
+    }
+
+    /// The position the game started from.
+    #[inline]
+    pub fn get_initial_board(&self) -> &BughouseBoard {
+        &self.initial
+    }
+
+    /// Play by `rules` from now on (see `BughouseBoard::set_rules`).
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.initial.set_rules(rules);
+        self.game.set_rules(rules);
+    }
+
+    #[inline]
+    pub fn get_rules(&self) -> &Rules {
+        self.get_board().get_rules()
+    }
+
+    pub fn side_to_move(&self) -> Color {
+        self.get_board().side_to_move()
+    }
+
+    #[inline]
+    pub fn get_result(&self) -> Option<&BughouseResult> {
+        self.game.get_result()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    /// Whether the side to move is checkmated.  Unlike in bughouse, there's
+    /// no partner to wait on: a mate that only a piece they don't hold could
+    /// block is mate.
+    pub fn is_mated(&self) -> bool {
+        self.game.is_mated(self.get_board())
+    }
+
+    pub fn resign(&mut self, color: Color) -> Result<(), Error> {
+        self.game.resign(Seat::new(BoardID::A, color))
+    }
+
+    pub fn agree_draw(&mut self) -> Result<(), Error> {
+        self.game.agree_draw()
+    }
+
+    /// Play `mv`, putting any captured piece in the mover's hand (as a pawn
+    /// if it was promoted, unless the rules say otherwise).  The outcome
+    /// records that as a transfer to the mover's own seat.  Checkmate and
+    /// stalemate end the game.
+    pub fn make_move(
+        &mut self,
+        mv: &BughouseMove,
+    ) -> Result<MoveOutcome, Error> {
+        self.game.play(BoardID::A, mv)
+    }
+
+    /// Every move played, in order.
+    #[inline]
+    pub fn get_history(&self) -> &[MoveOutcome] {
+        self.game.get_history()
+    }
+
+    /// Take back the last move (including any capture it added to the
+    /// mover's hand), resuming a game it ended.
+    pub fn unmake_move(&mut self) -> Option<MoveOutcome> {
+        self.game.unmake_move()
+    }
+
+    /// Set (or replace) a PGN header, e.g. `White` or `Event`
+    pub fn set_header(&mut self, key: &str, value: &str) {
+        set_header(&mut self.headers, key, value);
+    }
+
+    /// All headers (tag pairs), in the order they were read or set.
+    #[inline]
+    pub fn get_headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Look up a header, e.g. `White`, `Site` or `TimeControl`
+    pub fn get_header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Lichess style FEN, e.g.
+    /// `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1`
+    pub fn to_fen(&self) -> String {
+        self.get_board().to_bfen_as(HoldingsFormat::Bracket)
+    }
+
+    /// Parse FEN with holdings in any format `BughouseBoard::from_bfen`
+    /// accepts.
+    pub fn from_fen(fen: &str, mode: BfenMode) -> Result<Self, Error> {
+        BughouseBoard::from_bfen(fen, mode).map(CrazyhouseGame::new)
+    }
+
+    /// Write the game as PGN, as Lichess exports crazyhouse games: the
+    /// game's headers, with `Variant`, `Result` and (for non-standard
+    /// starting positions) `FEN` and `SetUp` brought up to date, then the
+    /// moves and result.
+    pub fn to_pgn(&self) -> String {
+        let result = self.get_result().map_or("*", |r| r.to_bpgn_result());
+        let mut headers = self.headers.clone();
+        set_header(&mut headers, "Variant", "Crazyhouse");
+        if self.initial != BughouseBoard::default() {
+            let fen = self.initial.to_bfen_as(HoldingsFormat::Bracket);
+            set_header(&mut headers, "FEN", &fen);
+            set_header(&mut headers, "SetUp", "1");
+        }
+        set_header(&mut headers, "Result", result);
+        let mut pgn: String = headers
+            .iter()
+            .map(|(key, value)| format_header(key, value))
+            .collect();
+        pgn.push('\n');
+
+        // Replay, crediting captures to the mover
+        let mut game = CrazyhouseGame::new(self.initial.clone());
+        let mut line = String::new();
+        let mut tokens = Vec::new();
+        for (idx, outcome) in self.get_history().iter().enumerate() {
+            let number = game.get_board().get_fullmove_number();
+            match game.side_to_move() {
+                Color::White => tokens.push(format!("{}.", number)),
+                Color::Black if idx == 0 => {
+                    tokens.push(format!("{}...", number))
+                }
+                Color::Black => {}
+            }
+            let mut san = outcome.get_move().to_ban(game.get_board());
+            game.make_move(outcome.get_move())
+                .expect("recorded moves are legal");
+            // Bughouse notation only knows mates no drop could block
+            if san.ends_with('+') && game.is_mated() {
+                san.pop();
+                san.push('#');
+            }
+            tokens.push(san);
+        }
+        tokens.push(result.to_string());
+        for token in tokens {
+            if !line.is_empty() && line.len() + token.len() + 1 > MAX_LINE_LEN {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+        pgn
+    }
+
+    /// Read a PGN game, e.g. as exported by Lichess, keeping its headers.
+    /// Comments (including clock and eval annotations), NAGs and variations
+    /// are skipped.  A
+    /// result that the moves don't explain is taken as a resignation or an
+    /// agreed draw.
+    pub fn from_pgn(pgn: &str) -> Result<Self, Error> {
+        let mut headers = Vec::new();
+        let mut movetext = String::new();
+        for line in pgn.lines() {
+            let trimmed = line.trim();
+            if movetext.trim().is_empty() && trimmed.starts_with('[') {
+                headers.push(parse_header(trimmed)?);
+            } else if !trimmed.starts_with('%') {
+                movetext.push_str(line);
+                movetext.push('\n');
+            }
+        }
+        let header = |key: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        if let Some(variant) = header("Variant") {
+            if !variant.eq_ignore_ascii_case("crazyhouse") {
+                return Err(Error::BpgnParseError(format!(
+                    "Not a crazyhouse game: {}",
+                    variant
+                )));
+            }
+        }
+        let mut game = match header("FEN") {
+            Some(fen) => CrazyhouseGame::from_str(fen)?,
+            None => CrazyhouseGame::default(),
+        };
+        game.headers = headers;
+
+        let mut result = None;
+        // How deep in (skipped) variations we are
+        let mut depth = 0usize;
+        let mut rest = movetext.as_str();
+        loop {
+            rest = rest.trim_start();
+            let first = match rest.chars().next() {
+                Some(c) => c,
+                None => break,
+            };
+            let skip_to = |end: char| {
+                rest.find(end).ok_or_else(|| {
+                    Error::BpgnParseError(format!("Unterminated: {}", rest))
+                })
+            };
+            match first {
+                '{' => {
+                    rest = &rest[skip_to('}')? + 1..];
+                    continue;
+                }
+                ';' => {
+                    rest = &rest[rest.find('\n').unwrap_or(rest.len())..];
+                    continue;
+                }
+                '(' => {
+                    depth += 1;
+                    rest = &rest[1..];
+                    continue;
+                }
+                ')' => {
+                    depth = depth.checked_sub(1).ok_or_else(|| {
+                        Error::BpgnParseError(format!("Unmatched ): {}", rest))
+                    })?;
+                    rest = &rest[1..];
+                    continue;
+                }
+                _ => {}
+            }
+            let end = rest
+                .find(|c: char| c.is_whitespace() || "{;()".contains(c))
+                .unwrap_or(rest.len());
+            let token = &rest[..end];
+            rest = &rest[end..];
+            if depth > 0 || token.starts_with('$') {
+                continue;
+            }
+            if result.is_some() {
+                return Err(Error::BpgnParseError(format!(
+                    "Unexpected text after result: {}",
+                    token
+                )));
+            }
+            if RESULTS.contains(&token) {
+                result = Some(token);
+                continue;
+            }
+            // Move numbers ("12." or "12..."), possibly run into the move
+            let san = token.trim_start_matches(|c: char| c.is_ascii_digit());
+            let san = if san.len() < token.len() {
+                san.trim_start_matches('.')
+            } else {
+                san
+            };
+            if san.is_empty() {
+                continue;
+            }
+            let fail = |err: Error| {
+                Error::BpgnMoveError(token.to_string(), Box::new(err))
+            };
+            let mv =
+                BughouseMove::from_ban(game.get_board(), san).map_err(fail)?;
+            game.make_move(&mv).map_err(fail)?;
+        }
+        if depth > 0 {
+            return Err(Error::BpgnParseError(
+                "Unterminated variation".to_string(),
+            ));
+        }
+        if !game.is_over() {
+            let loser = match result {
+                Some("1-0") => Some(Color::Black),
+                Some("0-1") => Some(Color::White),
+                Some("1/2-1/2") => {
+                    game.agree_draw()?;
+                    None
+                }
+                _ => None,
+            };
+            if let Some(color) = loser {
+                game.resign(color)?;
+            }
+        }
+        Ok(game)
+    }
+}
+
+impl FromStr for CrazyhouseGame {
+    type Err = Error;
+
+    /// Parse (leniently) a FEN position.
+    fn from_str(fen: &str) -> Result<Self, Self::Err> {
+        CrazyhouseGame::from_fen(fen, BfenMode::Lenient)
+    }
+}
+
+impl fmt::Display for CrazyhouseGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_board())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bughouse_move::get_mv;
+    use crate::game_result::Termination;
+    use chess::Piece;
+
+    #[test]
+    fn captures_go_to_the_capturer() {
+        let mut game = CrazyhouseGame::default();
+        for mv in ["e2e4", "d7d5", "e4d5", "d8d5", "b1c3"].iter() {
+            game.make_move(&get_mv(mv)).unwrap();
+        }
+        let outcome = game.make_move(&get_mv("d5a2")).unwrap();
+        let black = Seat::new(BoardID::A, Color::Black);
+        assert_eq!(outcome.get_transferred(), Some((black, Piece::Pawn)));
+        let holdings = game.get_board().get_holdings();
+        assert_eq!(holdings.to_string(), "Ppp");
+        game.make_move(&get_mv("a1a2")).unwrap();
+        assert_eq!(game.get_board().get_holdings().to_string(), "PQpp");
+        game.make_move(&get_mv("P@e4")).unwrap();
+        assert_eq!(game.get_board().get_holdings().to_string(), "PQp");
+
+        game.unmake_move();
+        game.unmake_move();
+        assert_eq!(game.get_board().get_holdings().to_string(), "Ppp");
+    }
+
+    #[test]
+    fn promoted_pieces_revert() {
+        let fen = "q~3k3/8/8/8/8/8/8/R3K3[] w - - 0 1";
+        let mut game = CrazyhouseGame::from_str(fen).unwrap();
+        let mut identity = game.clone();
+        game.make_move(&get_mv("a1a8")).unwrap();
+        assert_eq!(game.get_board().get_holdings().to_string(), "P");
+        identity.set_rules(Rules::new().with_promotions_revert(false));
+        identity.make_move(&get_mv("a1a8")).unwrap();
+        assert_eq!(identity.get_board().get_holdings().to_string(), "Q");
+    }
+
+    #[test]
+    fn full_hands_refuse_captures() {
+        let hand = "P".repeat(255);
+        let fen = format!("3k4/8/8/8/8/8/p7/R3K3[{}] w - - 0 1", hand);
+        let mut game = CrazyhouseGame::from_str(&fen).unwrap();
+        let before = game.clone();
+        assert!(matches!(
+            game.make_move(&get_mv("a1a2")),
+            Err(Error::HoldingsOverflow(Color::White, Piece::Pawn))
+        ));
+        assert_eq!(game, before);
+        assert!(game.get_history().is_empty());
+        assert!(game.make_move(&get_mv("P@e4")).is_ok());
+    }
+
+    #[test]
+    fn mates_without_partners() {
+        // Bughouse would wait for a piece to block with on b1
+        let fen = "3k4/8/8/8/8/8/r7/2K5[q] b - - 0 1";
+        let mut game = CrazyhouseGame::from_str(fen).unwrap();
+        let outcome = game.make_move(&get_mv("Q@a1")).unwrap();
+        assert!(outcome.is_mate());
+        let white = Seat::new(BoardID::A, Color::White);
+        assert_eq!(
+            game.get_result(),
+            Some(&BughouseResult::lost(white, Termination::Checkmate))
+        );
+        assert!(matches!(
+            game.make_move(&get_mv("c1d1")),
+            Err(Error::GameOver(_))
+        ));
+        game.unmake_move();
+        assert!(!game.is_over());
+        // Unless White has something to block with
+        let fen = "3k4/8/8/8/8/8/r7/2K5[Nq] b - - 0 1";
+        let mut game = CrazyhouseGame::from_str(fen).unwrap();
+        assert!(!game.make_move(&get_mv("Q@a1")).unwrap().is_mate());
+
+        let fen = "k7/8/8/8/8/8/8/2q5[] b - - 0 1";
+        assert!(CrazyhouseGame::from_str(fen).is_err());
+        let fen = "k7/8/8/8/8/8/3q4/K7[] b - - 0 1";
+        let mut game = CrazyhouseGame::from_str(fen).unwrap();
+        game.make_move(&get_mv("d2c2")).unwrap();
+        assert_eq!(game.get_result(), Some(&BughouseResult::stalemate()));
+    }
+
+    #[test]
+    fn lichess_fen() {
+        let fen =
+            "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R[Pp] \
+                   w KQkq - 4 5";
+        let game = CrazyhouseGame::from_str(fen).unwrap();
+        assert_eq!(game.to_fen(), fen);
+        let game = CrazyhouseGame::from_str(&fen.replace("[Pp]", "/Pp"));
+        assert_eq!(game.unwrap().to_fen(), fen);
+    }
+
+    #[test]
+    fn lichess_pgn() {
+        let pgn = r#"[Event "Rated Crazyhouse game"]
+[Site "https://lichess.org/abcdefgh"]
+[Variant "Crazyhouse"]
+[Result "1-0"]
+
+1. e4 { [%clk 0:03:00] } 1... e5 { [%clk 0:03:00] } 2. Nf3 Nc6 3. Bc4 Bc5
+4. Bxf7+ $1 Kxf7 5. Ng5+ (5. Nxe5+ Nxe5) 5... Ke8 6. P@f7+ Ke7 7. Nc3 d6
+8. Nd5+ Kd7 1-0"#;
+        let game = CrazyhouseGame::from_pgn(pgn).unwrap();
+        assert_eq!(game.get_history().len(), 16);
+        assert_eq!(game.get_board().get_holdings().to_string(), "b");
+        // Resigned, as far as the moves go
+        let black = Seat::new(BoardID::A, Color::Black);
+        assert_eq!(
+            game.get_result(),
+            Some(&BughouseResult::lost(black, Termination::Resignation))
+        );
+        let site = game.get_header("Site");
+        assert_eq!(site, Some("https://lichess.org/abcdefgh"));
+        let exported = game.to_pgn();
+        assert_eq!(
+            exported,
+            "[Event \"Rated Crazyhouse game\"]\n\
+             [Site \"https://lichess.org/abcdefgh\"]\n\
+             [Variant \"Crazyhouse\"]\n[Result \"1-0\"]\n\n\
+             1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. Bxf7+ Kxf7 5. Ng5+ Ke8 6. P@f7+ \
+             Ke7 7. Nc3 d6\n8. Nd5+ Kd7 1-0\n"
+        );
+        let reread = CrazyhouseGame::from_pgn(&exported).unwrap();
+        assert_eq!(reread, game);
+        assert_eq!(reread.get_headers(), game.get_headers());
+
+        let drawn = CrazyhouseGame::from_pgn("1. e4 e5 1/2-1/2").unwrap();
+        assert_eq!(drawn.get_result(), Some(&BughouseResult::drawn()));
+        let atomic = "[Variant \"Atomic\"]\n\n1. e4 *";
+        assert!(CrazyhouseGame::from_pgn(atomic).is_err());
+        assert!(CrazyhouseGame::from_pgn("1. e5").is_err());
+        // Variations must be balanced
+        assert!(CrazyhouseGame::from_pgn("1. e4 ) e5 *").is_err());
+        assert!(CrazyhouseGame::from_pgn("1. e4 (1. d4 d5 *").is_err());
+        assert!(CrazyhouseGame::from_pgn("1. e4 (1. d4 (1. c4)) e5 *").is_ok());
+    }
+
+    #[test]
+    fn mates_are_written_as_mates() {
+        // Bughouse notation would only call this check
+        let pgn = "[FEN \"3k4/8/8/8/8/8/r7/2K5[q] b - - 0 1\"]\n\n1... Q@a1+";
+        let game = CrazyhouseGame::from_pgn(pgn).unwrap();
+        assert!(game.is_mated());
+        assert!(game.to_pgn().ends_with("\n1... Q@a1# 0-1\n"));
+    }
+
+    #[test]
+    fn setup_positions_round_trip() {
+        let fen = "4k3/8/8/8/8/8/8/4K3[Nn] b - - 0 30";
+        let mut game = CrazyhouseGame::from_str(fen).unwrap();
+        game.make_move(&get_mv("N@e6")).unwrap();
+        let pgn = game.to_pgn();
+        assert!(pgn.contains("[FEN \"4k3/8/8/8/8/8/8/4K3[Nn] b - - 0 30\"]"));
+        assert!(pgn.ends_with("\n30... N@e6 *\n"));
+        assert_eq!(CrazyhouseGame::from_pgn(&pgn).unwrap(), game);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::bughouse_game::{BoardID, MultiBoardID, MultiSeat};
+use crate::game_result::BughouseResult;
+use std::fmt;
 use thiserror::Error;
 
 fn color_to_str(c: chess::Color) -> String {
@@ -7,17 +10,107 @@ fn color_to_str(c: chess::Color) -> String {
   }
 }
 
+/// The fields of a BFEN board, for locating parse errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BfenField {
+    Placement,
+    Holdings,
+    SideToMove,
+    Castling,
+    EnPassant,
+    HalfmoveClock,
+    FullmoveNumber,
+}
+
+impl fmt::Display for BfenField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            BfenField::Placement => "piece placement",
+            BfenField::Holdings => "holdings",
+            BfenField::SideToMove => "side to move",
+            BfenField::Castling => "castling",
+            BfenField::EnPassant => "en passant",
+            BfenField::HalfmoveClock => "halfmove clock",
+            BfenField::FullmoveNumber => "fullmove number",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Why a move was rejected by `BughouseBoard::check_move`.  (Moving out of
+/// turn and moving after the game has ended are reported by `BughouseGame`
+/// as `Error::OutOfTurn` and `Error::GameOver`.)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Error)]
+pub enum IllegalMoveReason {
+    #[error("it's the other side's move")]
+    WrongSideToMove,
+
+    #[error("there's no piece on {0}")]
+    NoPiece(chess::Square),
+
+    #[error("the piece can't move there")]
+    InvalidPieceMove,
+
+    #[error("{} {1} isn't held", color_to_str(*.0))]
+    NotHeld(chess::Color, chess::Piece),
+
+    #[error("{0} is occupied")]
+    Occupied(chess::Square),
+
+    #[error("pawns can't be dropped on that rank")]
+    PawnDropRank,
+
+    #[error("the drop doesn't block the check")]
+    DropDoesNotResolveCheck,
+
+    #[error("drops can't give checkmate")]
+    DropMate,
+
+    #[error("the king would be in check")]
+    LeavesKingInCheck,
+}
+
+/// Something about a position that play couldn't have led to, found by
+/// `BughouseGame::is_sane`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Error)]
+pub enum SanityViolation {
+    /// More of one color's piece type on the boards and in hand than two
+    /// chess sets have.  Promoted pieces count as pawns.
+    #[error(
+        "{2} {} {1:?}s in play, more than two sets have",
+        color_to_str(*.0)
+    )]
+    TooManyPieces(chess::Color, chess::Piece, u32),
+
+    #[error("pawn on {1} on board {0}")]
+    PawnOnBackRank(BoardID, chess::Square),
+}
+
 #[derive(Clone, Debug, Error)]
 pub enum Error {
     /// The FEN string is invalid
     #[error("Invalid Game BFEN")]
     GameParseError(String),
 
-    #[error("Invalid Board BFEN: {0}")]
-    BoardParseError(String),
+    /// `offset` counts characters into `input`
+    #[error("Invalid Board BFEN {field} at character {offset}: {input}")]
+    BoardParseError {
+        input: String,
+        field: BfenField,
+        offset: usize,
+    },
+
+    #[error("Illegal move {0}: {1}")]
+    IllegalMove(String, IllegalMoveReason),
 
-    #[error("Illegal move: {0}")]
-    IllegalMove(String),
+    #[error("Not {0}'s turn")]
+    OutOfTurn(MultiSeat),
+
+    #[error("No board {0} in this game")]
+    UnknownBoard(MultiBoardID),
+
+    #[error("Game over: {0}")]
+    GameOver(BughouseResult<MultiBoardID>),
 
     #[error("Can't parse move: {0}")]
     MoveParseError(String),
@@ -25,13 +118,45 @@ pub enum Error {
     #[error("Unheld Drop: {} {1}", color_to_str(*.0))]
     UnheldDrop(chess::Color, chess::Piece),
 
+    #[error("Holdings overflow: {} {1}", color_to_str(*.0))]
+    HoldingsOverflow(chess::Color, chess::Piece),
+
+    #[error("Invalid BPGN: {0}")]
+    BpgnParseError(String),
+
+    #[error("Invalid BPGN move {0}: {1}")]
+    BpgnMoveError(String, Box<Error>),
+
+    #[error("Invalid time control: {0}")]
+    TimeControlParseError(String),
+
     #[error("Invalid holdings: {0}")]
     HoldingsParseError(String),
 
+    #[error("Invalid capture routing: {0}")]
+    RoutingError(String),
+
     #[error("Chess Error: {0}")]
     Chess(chess::Error),
 }
 
+impl Error {
+    // Relocate a BFEN error within the larger `input` it was found in, e.g.
+    // board B of a game
+    pub(crate) fn shift_bfen_error(self, input: &str, shift: usize) -> Self {
+        match self {
+            Error::BoardParseError { field, offset, .. } => {
+                Error::BoardParseError {
+                    input: input.to_string(),
+                    field,
+                    offset: offset + shift,
+                }
+            }
+            err => err,
+        }
+    }
+}
+
 impl From<chess::Error> for Error {
     fn from(err: chess::Error) -> Self {
         Error::Chess(err)
@@ -0,0 +1,124 @@
+use crate::bughouse_game::{
+    BoardID, GameBoardID, MultiBoardID, Seat, Team,
+};
+use std::fmt;
+
+/// How a bughouse game ended.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub enum Termination {
+    Checkmate,
+    Flag,
+    Resignation,
+    /// Ended before a result (e.g. a player disconnected on move one)
+    Abort,
+    /// Drawn by agreement
+    Agreement,
+    /// Drawn with no legal move or drop (in crazyhouse, where nobody will
+    /// send a piece)
+    Stalemate,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Flag => "flag",
+            Termination::Resignation => "resignation",
+            Termination::Abort => "abort",
+            Termination::Agreement => "agreement",
+            Termination::Stalemate => "stalemate",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The outcome of a finished game, decided across both boards (or all of
+/// them, in a `MultiBughouseGame`).
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub struct BughouseResult<B = BoardID> {
+    // The seat that lost the game for their team
+    loser: Option<Seat<B>>,
+    reason: Termination,
+}
+
+impl<B: GameBoardID> BughouseResult<B> {
+    /// `seat` lost, costing their team the game.
+    pub fn lost(seat: Seat<B>, reason: Termination) -> Self {
+        BughouseResult {
+            loser: Some(seat),
+            reason,
+        }
+    }
+
+    pub fn aborted() -> Self {
+        BughouseResult {
+            loser: None,
+            reason: Termination::Abort,
+        }
+    }
+
+    pub fn drawn() -> Self {
+        BughouseResult {
+            loser: None,
+            reason: Termination::Agreement,
+        }
+    }
+
+    pub fn stalemate() -> Self {
+        BughouseResult {
+            loser: None,
+            reason: Termination::Stalemate,
+        }
+    }
+
+    #[inline]
+    pub fn get_loser(&self) -> Option<Seat<B>> {
+        self.loser
+    }
+
+    #[inline]
+    pub fn get_reason(&self) -> Termination {
+        self.reason
+    }
+
+    /// The winning team, if there is one.
+    pub fn get_winner(&self) -> Option<Team> {
+        self.loser.map(|seat| !seat.team())
+    }
+
+    /// BPGN result string, from the perspective of White on board A
+    pub fn to_bpgn_result(self) -> &'static str {
+        match (self.get_winner(), self.reason) {
+            (Some(Team::One), _) => "1-0",
+            (Some(Team::Two), _) => "0-1",
+            (None, Termination::Agreement) | (None, Termination::Stalemate) => {
+                "1/2-1/2"
+            }
+            (None, _) => "*",
+        }
+    }
+
+    /// The same result, with the loser seated as in a `MultiBughouseGame`.
+    pub fn to_multi(&self) -> BughouseResult<MultiBoardID> {
+        BughouseResult {
+            loser: self.loser.map(|seat| seat.to_multi()),
+            reason: self.reason,
+        }
+    }
+}
+
+impl<B: GameBoardID> fmt::Display for BughouseResult<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.loser, self.get_winner()) {
+            (Some(seat), Some(team)) => write!(
+                f,
+                "{} wins: {:?} on board {} lost by {}",
+                team,
+                seat.get_color(),
+                seat.get_board(),
+                self.reason
+            ),
+            _ => write!(f, "No winner: {}", self.reason),
+        }
+    }
+}
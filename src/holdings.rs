@@ -1,5 +1,6 @@
 use chess::{Color, Piece, ALL_PIECES, ALL_COLORS, NUM_COLORS}; // , NUM_PIECES};
 use crate::error::*;
+use crate::zobrist;
 use std::str::FromStr;
 use std::fmt;
 
@@ -14,15 +15,36 @@ fn empty() -> HeldArray {
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Holdings {
     holdings: HeldArray,
+    // Kept up to date by `add` and `drop`
+    hash: u64,
 }
 
 impl Holdings {
     pub fn new(holdings: &HeldArray) -> Self {
+        let mut hash = 0u64;
+        for (c, pieces) in holdings.iter().enumerate() {
+            for (p, count) in pieces.iter().enumerate() {
+                let key = zobrist::holdings_key(ALL_COLORS[c], ALL_PIECES[p]);
+                hash = hash.wrapping_add(key.wrapping_mul(u64::from(*count)));
+            }
+        }
         Holdings {
             holdings: *holdings,
+            hash,
         }
     }
 
+    /// Zobrist style hash of the held piece counts.
+    #[inline]
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// How many of `piece` `color` holds.
+    pub fn count(&self, color: Color, piece: Piece) -> u8 {
+        self.holdings[color.to_index()][piece.to_index()]
+    }
+
     pub fn has_piece(&self, color: Color, piece: Piece) -> bool {
         self.holdings[color.to_index()][piece.to_index()] > 0
     }
@@ -33,15 +55,81 @@ impl Holdings {
         let cur_val = self.holdings[color_idx][piece_idx];
         if cur_val > 0 {
             self.holdings[color_idx][piece_idx] = cur_val - 1;
+            self.hash =
+                self.hash.wrapping_sub(zobrist::holdings_key(color, piece));
             return Ok(());
         }
-        return Err(Error::UnheldDrop(color, piece));
+        Err(Error::UnheldDrop(color, piece))
+    }
+
+    // Parse held pieces, or fail with the (character) offset of the first one
+    // that isn't a piece that can be held
+    pub(crate) fn parse(value: &str) -> Result<Self, usize> {
+        let mut bfen_holdings = empty();
+        for (i, ch) in value.chars().enumerate() {
+            let piece = match ch.to_ascii_lowercase() {
+                'p' => Piece::Pawn,
+                'n' => Piece::Knight,
+                'b' => Piece::Bishop,
+                'r' => Piece::Rook,
+                'q' => Piece::Queen,
+                _ => return Err(i),
+            };
+            let color = if ch.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let count = &mut bfen_holdings[color.to_index()][piece.to_index()];
+            *count = count.checked_add(1).ok_or(i)?;
+        }
+        Ok(Holdings::new(&bfen_holdings))
     }
 
-    pub fn add(&mut self, color: Color, piece: Piece) {
-        let cidx = color.to_index();
-        let pidx = piece.to_index();
-        self.holdings[cidx][pidx] += 1;
+    /// Give `color` another `piece`, unless they already hold as many as can
+    /// be counted.
+    pub fn add(&mut self, color: Color, piece: Piece) -> Result<(), Error> {
+        let count = &mut self.holdings[color.to_index()][piece.to_index()];
+        *count = count
+            .checked_add(1)
+            .ok_or(Error::HoldingsOverflow(color, piece))?;
+        self.hash = self.hash.wrapping_add(zobrist::holdings_key(color, piece));
+        Ok(())
+    }
+}
+
+/// Where a BFEN board writes its holdings.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum HoldingsFormat {
+    /// A 0th rank after an 8th slash (the BPGN standard), e.g.
+    /// `.../RNBQKBNR/Qp w KQkq - 0 1`
+    Slash,
+    /// In brackets after the 1st rank (as Lichess' crazyhouse does), e.g.
+    /// `.../RNBQKBNR[Qp] w KQkq - 0 1`
+    Bracket,
+    /// A final field after the move counters (as chess.com does), with `-`
+    /// for none, e.g. `.../RNBQKBNR w KQkq - 0 1 Qp`
+    Suffix,
+}
+
+impl HoldingsFormat {
+    /// Guess the format of one board of BFEN.  Boards without any holdings
+    /// section are `Slash`, with the holdings inferred to be empty.
+    pub fn detect(bfen: &str) -> HoldingsFormat {
+        let mut fields = bfen.split_whitespace();
+        let placement = fields.next().unwrap_or("");
+        if placement.contains('[') {
+            return HoldingsFormat::Bracket;
+        }
+        if placement.matches('/').count() < 8 {
+            // Past side to move, castling, en passant and the counters
+            if let Some(last) = fields.nth(5) {
+                if last == "-" || Holdings::parse(last).is_ok() {
+                    return HoldingsFormat::Suffix;
+                }
+            }
+        }
+        HoldingsFormat::Slash
     }
 }
 
@@ -49,7 +137,10 @@ impl Holdings {
 impl Default for Holdings {
     #[inline]
     fn default() -> Self {
-        Holdings { holdings: empty() }
+        Holdings {
+            holdings: empty(),
+            hash: 0,
+        }
     }
 }
 
@@ -63,30 +154,8 @@ impl FromStr for Holdings {
     ///
     /// Chess.com apparently uses FEN notation with the holdings section following
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        // value
-        let mut bfen_holdings = empty();
-        let black_idx = Color::Black.to_index();
-        let white_idx = Color::White.to_index();
-        for p in value.trim().chars() {
-            match p {
-                'p' => bfen_holdings[black_idx][Piece::Pawn.to_index()] += 1,
-                'n' => bfen_holdings[black_idx][Piece::Knight.to_index()] += 1,
-                'b' => bfen_holdings[black_idx][Piece::Bishop.to_index()] += 1,
-                'r' => bfen_holdings[black_idx][Piece::Rook.to_index()] += 1,
-                'q' => bfen_holdings[black_idx][Piece::Queen.to_index()] += 1,
-                'P' => bfen_holdings[white_idx][Piece::Pawn.to_index()] += 1,
-                'N' => bfen_holdings[white_idx][Piece::Knight.to_index()] += 1,
-                'B' => bfen_holdings[white_idx][Piece::Bishop.to_index()] += 1,
-                'R' => bfen_holdings[white_idx][Piece::Rook.to_index()] += 1,
-                'Q' => bfen_holdings[white_idx][Piece::Queen.to_index()] += 1,
-                _ => {
-                    return Err(Error::HoldingsParseError(value.to_string()));
-                }
-            }
-        }
-        Ok(Holdings {
-            holdings: bfen_holdings,
-        })
+        Holdings::parse(value.trim())
+            .map_err(|_| Error::HoldingsParseError(value.to_string()))
     }
 }
 
@@ -128,6 +197,49 @@ fn kings_dont_make_sense() {
     assert!(Holdings::from_str("k").is_err());
 }
 
+#[test]
+fn incremental_hash() {
+    let mut res = Holdings::from_str("BrpBBqppN").unwrap();
+    res.add(Color::White, Piece::Pawn).unwrap();
+    res.drop(Color::Black, Piece::Queen).unwrap();
+    assert_eq!(res.get_hash(), Holdings::from_str("PNBBBpppr").unwrap().get_hash());
+    assert_ne!(res.get_hash(), Holdings::from_str("PNBBBppr").unwrap().get_hash());
+    assert_ne!(res.get_hash(), Holdings::from_str("pNBBBpppr").unwrap().get_hash());
+    assert_eq!(Holdings::default().get_hash(), Holdings::from_str("").unwrap().get_hash());
+}
+
+#[test]
+fn counts_dont_overflow() {
+    let mut res = Holdings::default();
+    for _ in 0..u8::MAX {
+        res.add(Color::Black, Piece::Queen).unwrap();
+    }
+    let full = res.clone();
+    assert!(matches!(
+        res.add(Color::Black, Piece::Queen),
+        Err(Error::HoldingsOverflow(Color::Black, Piece::Queen))
+    ));
+    assert_eq!(res, full);
+    assert_eq!(res.count(Color::Black, Piece::Queen), u8::MAX);
+    assert!(Holdings::from_str(&"q".repeat(256)).is_err());
+}
+
+#[test]
+fn detect_formats() {
+    let cases = [
+        ("8/8/8/8/8/8/8/8/Qp w - - 0 1", HoldingsFormat::Slash),
+        ("8/8/8/8/8/8/8/8 w - - 0 1", HoldingsFormat::Slash),
+        ("8/8/8/8/8/8/8/8 w - - 0 1 180 175", HoldingsFormat::Slash),
+        ("8/8/8/8/8/8/8/8[Qp] w - - 0 1", HoldingsFormat::Bracket),
+        ("8/8/8/8/8/8/8/8[] w - - 0 1", HoldingsFormat::Bracket),
+        ("8/8/8/8/8/8/8/8 w - - 0 1 Qp", HoldingsFormat::Suffix),
+        ("8/8/8/8/8/8/8/8 w - - 0 1 -", HoldingsFormat::Suffix),
+    ];
+    for (bfen, format) in &cases {
+        assert_eq!(HoldingsFormat::detect(bfen), *format, "{}", bfen);
+    }
+}
+
 #[test]
 fn holdings_to_str() {
     let res = Holdings::from_str("BrpBBqppN").unwrap();
@@ -1,12 +1,18 @@
-use chess;
-
 #[macro_use]
 extern crate lazy_static;
 
 pub use chess::*;
 
 mod error;
-pub use crate::error::Error;
+pub use crate::error::{BfenField, Error, IllegalMoveReason, SanityViolation};
+
+mod zobrist;
+
+mod rules;
+pub use crate::rules::*;
+
+mod chess960;
+pub use crate::chess960::*;
 
 mod holdings;
 pub use crate::holdings::*;
@@ -20,5 +26,38 @@ pub use crate::bughouse_move::*;
 mod bughouse_board;
 pub use crate::bughouse_board::*;
 
+mod bughouse_movegen;
+pub use crate::bughouse_movegen::*;
+
+mod perft;
+pub use crate::perft::*;
+
+mod bpgn;
+pub use crate::bpgn::*;
+
 mod bughouse_game;
 pub use crate::bughouse_game::*;
+
+mod capture_routing;
+pub use crate::capture_routing::*;
+
+mod clock;
+pub use crate::clock::*;
+
+mod move_outcome;
+pub use crate::move_outcome::*;
+
+mod terminal;
+pub use crate::terminal::*;
+
+mod svg;
+pub use crate::svg::*;
+
+mod crazyhouse_game;
+pub use crate::crazyhouse_game::*;
+
+mod multi_bughouse_game;
+pub use crate::multi_bughouse_game::*;
+
+mod game_result;
+pub use crate::game_result::*;
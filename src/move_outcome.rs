@@ -0,0 +1,122 @@
+use crate::bughouse_game::{BoardID, GameBoardID, Seat};
+use crate::bughouse_move::BughouseMove;
+use chess::{Piece, Square};
+
+/// A change to one seat's holdings caused by a move.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub struct HoldingsDelta<B = BoardID> {
+    seat: Seat<B>,
+    piece: Piece,
+    delta: i8,
+}
+
+impl<B: GameBoardID> HoldingsDelta<B> {
+    pub fn new(seat: Seat<B>, piece: Piece, delta: i8) -> Self {
+        HoldingsDelta { seat, piece, delta }
+    }
+
+    /// Whose holdings changed.
+    #[inline]
+    pub fn get_seat(&self) -> Seat<B> {
+        self.seat
+    }
+
+    #[inline]
+    pub fn get_piece(&self) -> Piece {
+        self.piece
+    }
+
+    /// -1 for a drop, +1 for a piece received from the partner's board.
+    #[inline]
+    pub fn get_delta(&self) -> i8 {
+        self.delta
+    }
+}
+
+/// Everything that happened as a result of `BughouseGame::make_move`, for
+/// animating transfers and broadcasting diffs.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct MoveOutcome<B = BoardID> {
+    seat: Seat<B>,
+    mv: BughouseMove,
+    captured: Option<(Piece, Square)>,
+    transferred: Option<(Seat<B>, Piece)>,
+    is_check: bool,
+    is_mate: bool,
+    holdings_deltas: Vec<HoldingsDelta<B>>,
+}
+
+impl<B: GameBoardID> MoveOutcome<B> {
+    pub(crate) fn new(seat: Seat<B>, mv: BughouseMove) -> Self {
+        MoveOutcome {
+            seat,
+            mv,
+            captured: None,
+            transferred: None,
+            is_check: false,
+            is_mate: false,
+            holdings_deltas: Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_captured(&mut self, piece: Piece, sq: Square) {
+        self.captured = Some((piece, sq));
+    }
+
+    pub(crate) fn set_transferred(&mut self, to: Seat<B>, piece: Piece) {
+        self.transferred = Some((to, piece));
+        self.holdings_deltas.push(HoldingsDelta::new(to, piece, 1));
+    }
+
+    pub(crate) fn set_dropped(&mut self, piece: Piece) {
+        self.holdings_deltas
+            .push(HoldingsDelta::new(self.seat, piece, -1));
+    }
+
+    pub(crate) fn set_check(&mut self, is_check: bool, is_mate: bool) {
+        self.is_check = is_check;
+        self.is_mate = is_mate;
+    }
+
+    /// The seat that moved.
+    #[inline]
+    pub fn get_seat(&self) -> Seat<B> {
+        self.seat
+    }
+
+    #[inline]
+    pub fn get_move(&self) -> &BughouseMove {
+        &self.mv
+    }
+
+    /// The captured piece as it stood on the board, and the square it was
+    /// captured on (which differs from the destination for en passant).
+    #[inline]
+    pub fn get_captured(&self) -> Option<(Piece, Square)> {
+        self.captured
+    }
+
+    /// The partner who received the captured piece, and what they received
+    /// (a pawn, if the captured piece was promoted).
+    #[inline]
+    pub fn get_transferred(&self) -> Option<(Seat<B>, Piece)> {
+        self.transferred
+    }
+
+    /// Whether the opponent is now in check.
+    #[inline]
+    pub fn is_check(&self) -> bool {
+        self.is_check
+    }
+
+    /// Whether the opponent is now mated (see `BughouseBoard::is_mated`).
+    #[inline]
+    pub fn is_mate(&self) -> bool {
+        self.is_mate
+    }
+
+    #[inline]
+    pub fn get_holdings_deltas(&self) -> &[HoldingsDelta<B>] {
+        &self.holdings_deltas
+    }
+}
@@ -0,0 +1,530 @@
+use crate::bughouse_board::{BfenMode, BoardUndo, BughouseBoard, MateStatus};
+use crate::bughouse_game::{
+    GameBoardID, MultiBoardID, MultiSeat, Seat, MAX_BOARDS,
+};
+use crate::bughouse_move::BughouseMove;
+use crate::capture_routing::CaptureRouting;
+use crate::chess960::CastlingNotation;
+use crate::error::*;
+use crate::game_result::{BughouseResult, Termination};
+use crate::holdings::HoldingsFormat;
+use crate::move_outcome::MoveOutcome;
+use crate::rules::Rules;
+use chess::Piece;
+use std::str::FromStr;
+
+// What `unmake_move` needs to take back a move, besides its `MoveOutcome`
+#[derive(Clone, Debug)]
+struct Undo<B> {
+    // Takes back the move on the moved board
+    board: BoardUndo,
+    result: Option<BughouseResult<B>>,
+}
+
+/// Bughouse generalized to any number of boards, e.g. three boards for six
+/// players.  Two teams play, alternating colors from board to board (see
+/// `Team`), and captured pieces go wherever the `CaptureRouting` says.  As
+/// in bughouse, a mate on any board ends the game.
+///
+/// This is the game `BughouseGame` (two boards, round robin) and
+/// `CrazyhouseGame` (one board) play, naming boards by `BoardID` rather
+/// than `MultiBoardID`.  On a single board nobody else can send a piece, so
+/// a mate that only a drop could block is mate, and having no move is
+/// stalemate.
+///
+/// Positions are read and written as each board's BFEN, separated by
+/// `" | "`.  (BPGN and clocks remain two-board only.)
+///
+/// ```
+/// use bughouse::{BughouseMove, MultiBoardID, MultiBughouseGame};
+/// use chess::{Color, Piece};
+/// use std::str::FromStr;
+///
+/// let mut game = MultiBughouseGame::new(3).unwrap();
+/// let board_c = MultiBoardID::new(2).unwrap();
+/// for mv in ["e2e4", "d7d5", "e4d5"].iter() {
+///     let mv = BughouseMove::from_str(mv).unwrap();
+///     game.make_move(board_c, &mv).unwrap();
+/// }
+/// // White on C passes to their teammate, White on A
+/// let holdings = game.get_board(MultiBoardID::A).unwrap().get_holdings();
+/// assert_eq!(holdings.count(Color::White, Piece::Pawn), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultiBughouseGame<B = MultiBoardID> {
+    boards: Vec<BughouseBoard>,
+    routing: CaptureRouting,
+    result: Option<BughouseResult<B>>,
+    history: Vec<MoveOutcome<B>>,
+    undos: Vec<Undo<B>>,
+}
+
+// History is deliberately left out: two games are equal if they're in the
+// same state, however they got there.
+impl<B: GameBoardID> PartialEq for MultiBughouseGame<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.boards == other.boards
+            && self.routing == other.routing
+            && self.result == other.result
+    }
+}
+
+impl<B: GameBoardID> Eq for MultiBughouseGame<B> {}
+
+impl MultiBughouseGame {
+    /// `num_boards` boards in the initial position, passing captures round
+    /// robin (see `CaptureRouting::round_robin`).
+    pub fn new(num_boards: usize) -> Result<Self, Error> {
+        MultiBughouseGame::check_board_count(num_boards)?;
+        let boards = vec![BughouseBoard::default(); num_boards];
+        let routing = CaptureRouting::round_robin(num_boards);
+        MultiBughouseGame::with_routing(boards, routing)
+    }
+
+    /// Play from `boards`, passing captures by `routing`, which must be for
+    /// as many boards.
+    pub fn with_routing(
+        boards: Vec<BughouseBoard>,
+        routing: CaptureRouting,
+    ) -> Result<Self, Error> {
+        let num_boards = boards.len();
+        MultiBughouseGame::check_board_count(num_boards)?;
+        if routing.num_boards() != num_boards {
+            let msg = format!(
+                "{} boards routed, but {} played",
+                routing.num_boards(),
+                num_boards
+            );
+            return Err(Error::RoutingError(msg));
+        }
+        Ok(MultiBughouseGame::from_parts(boards, routing))
+    }
+
+    fn check_board_count(num_boards: usize) -> Result<(), Error> {
+        if num_boards == 0 || num_boards > MAX_BOARDS {
+            let msg = format!("{} boards", num_boards);
+            return Err(Error::RoutingError(msg));
+        }
+        Ok(())
+    }
+
+    /// The IDs of the boards in play, from A.
+    pub fn get_board_ids(&self) -> Vec<MultiBoardID> {
+        (0..self.boards.len()).map(MultiBoardID::from_index).collect()
+    }
+
+    /// Board `id`, if it's in play.
+    pub fn get_board(&self, id: MultiBoardID) -> Option<&BughouseBoard> {
+        self.boards.get(id.to_index())
+    }
+
+    /// The seat whose turn it is on `board`, if it's in play.
+    pub fn seat_to_move(&self, board: MultiBoardID) -> Option<MultiSeat> {
+        let bug_board = self.get_board(board)?;
+        Some(Seat::new(board, bug_board.side_to_move()))
+    }
+
+    /// Play `mv` as `seat`, rejecting it if it isn't their turn.
+    pub fn make_seat_move(
+        &mut self,
+        seat: MultiSeat,
+        mv: &BughouseMove,
+    ) -> Result<MoveOutcome<MultiBoardID>, Error> {
+        let board = seat.get_board();
+        let to_move =
+            self.seat_to_move(board).ok_or(Error::UnknownBoard(board))?;
+        if to_move != seat {
+            return Err(Error::OutOfTurn(seat));
+        }
+        self.play(board, mv)
+    }
+
+    /// Play `mv` on board `name`, passing any captured piece on as routed.
+    /// Fails if `name` isn't in play, or once the game is over; a mate on
+    /// any board ends it.
+    pub fn make_move(
+        &mut self,
+        name: MultiBoardID,
+        mv: &BughouseMove,
+    ) -> Result<MoveOutcome<MultiBoardID>, Error> {
+        if self.get_board(name).is_none() {
+            return Err(Error::UnknownBoard(name));
+        }
+        self.play(name, mv)
+    }
+
+    /// Parse boards separated by `" | "`, passing captures round robin.
+    /// Errors in later boards are located relative to the whole input.
+    pub fn from_bfen(input_str: &str, mode: BfenMode) -> Result<Self, Error> {
+        let mut boards = Vec::new();
+        let mut shift = 0;
+        for board_str in input_str.split(" | ") {
+            let board = BughouseBoard::from_bfen(board_str, mode)
+                .map_err(|e| e.shift_bfen_error(input_str, shift))?;
+            boards.push(board);
+            shift += board_str.chars().count() + 3;
+        }
+        let routing = CaptureRouting::round_robin(boards.len());
+        MultiBughouseGame::with_routing(boards, routing)
+    }
+}
+
+impl<B: GameBoardID> MultiBughouseGame<B> {
+    // `boards` named by `B`, with `routing` for as many of them
+    pub(crate) fn from_parts(
+        boards: Vec<BughouseBoard>,
+        routing: CaptureRouting,
+    ) -> Self {
+        MultiBughouseGame {
+            boards,
+            routing,
+            result: None,
+            history: Vec::new(),
+            undos: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn num_boards(&self) -> usize {
+        self.boards.len()
+    }
+
+    /// Every board, A first.
+    #[inline]
+    pub fn get_boards(&self) -> &[BughouseBoard] {
+        &self.boards
+    }
+
+    #[inline]
+    pub fn get_routing(&self) -> &CaptureRouting {
+        &self.routing
+    }
+
+    /// Play every board by `rules` (see `BughouseBoard::set_rules`).
+    pub fn set_rules(&mut self, rules: Rules) {
+        for board in self.boards.iter_mut() {
+            board.set_rules(rules);
+        }
+    }
+
+    // Board `id`, which `B` names only boards in play for
+    pub(crate) fn board(&self, id: B) -> &BughouseBoard {
+        &self.boards[id.to_index()]
+    }
+
+    // The seat whose turn it is on `board`, one of `board`'s
+    pub(crate) fn to_move(&self, board: B) -> Seat<B> {
+        Seat::new(board, self.board(board).side_to_move())
+    }
+
+    #[inline]
+    pub fn get_result(&self) -> Option<&BughouseResult<B>> {
+        self.result.as_ref()
+    }
+
+    #[inline]
+    pub fn is_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// `seat` resigns for their team.
+    pub fn resign(&mut self, seat: Seat<B>) -> Result<(), Error> {
+        self.try_end(BughouseResult::lost(seat, Termination::Resignation))
+    }
+
+    pub fn abort(&mut self) -> Result<(), Error> {
+        self.try_end(BughouseResult::aborted())
+    }
+
+    pub fn agree_draw(&mut self) -> Result<(), Error> {
+        self.try_end(BughouseResult::drawn())
+    }
+
+    fn try_end(&mut self, result: BughouseResult<B>) -> Result<(), Error> {
+        if let Some(result) = self.result {
+            return Err(Error::GameOver(result.to_multi()));
+        }
+        self.end(result);
+        Ok(())
+    }
+
+    // End the game however it stands, e.g. on a flag
+    pub(crate) fn end(&mut self, result: BughouseResult<B>) {
+        self.result = Some(result);
+    }
+
+    /// Whether the side to move on `board` (one of this game's) is
+    /// checkmated.
+    pub(crate) fn is_mated(&self, board: &BughouseBoard) -> bool {
+        match board.mate_status() {
+            MateStatus::Mated => true,
+            MateStatus::MatedUnless(_) => self.boards.len() == 1,
+            _ => false,
+        }
+    }
+
+    // Play `mv` on board `name`, which must be in play (see `make_move`)
+    pub(crate) fn play(
+        &mut self,
+        name: B,
+        mv: &BughouseMove,
+    ) -> Result<MoveOutcome<B>, Error> {
+        if let Some(result) = self.result {
+            return Err(Error::GameOver(result.to_multi()));
+        }
+        let bug_board = &mut self.boards[name.to_index()];
+        let mover = Seat::new(name, bug_board.side_to_move());
+        let opp = !mover.get_color();
+        let capture = bug_board.get_capture(mv);
+        let is_promo = capture
+            .map_or(false, |(_, sq)| bug_board.get_promos().is_promo(opp, sq));
+        let reverts = is_promo && bug_board.get_rules().promotions_revert();
+        let undo = Undo {
+            board: bug_board.make_move(mv)?,
+            result: self.result,
+        };
+
+        let mut outcome = MoveOutcome::new(mover, *mv);
+        if mv.get_source().is_none() {
+            outcome.set_dropped(mv.get_piece().unwrap());
+        }
+        if let Some((piece, sq)) = capture {
+            outcome.set_captured(piece, sq);
+            let routed = self.routing.get_receiver(mover.to_multi());
+            let receiver = Seat::new(
+                B::from_index(routed.get_board().to_index()),
+                routed.get_color(),
+            );
+            let piece = if reverts { Piece::Pawn } else { piece };
+            let added = self.boards[receiver.get_board().to_index()]
+                .holdings()
+                .add(receiver.get_color(), piece);
+            if let Err(e) = added {
+                // Only drops put a piece back in hand, and this was a capture
+                self.boards[name.to_index()]
+                    .unmake_move(undo.board)
+                    .expect("a capture has no piece to put back in hand");
+                return Err(e);
+            }
+            outcome.set_transferred(receiver, piece);
+        }
+        let moved = self.board(name);
+        let is_mate = self.is_mated(moved);
+        outcome.set_check(moved.in_check(), is_mate);
+        // Besides the moved board, only one a transfer reached has changed
+        let mated_board = if is_mate {
+            Some(name)
+        } else {
+            outcome
+                .get_transferred()
+                .map(|(receiver, _)| receiver.get_board())
+                .filter(|board| *board != name)
+                .filter(|board| self.is_mated(self.board(*board)))
+        };
+        if let Some(board) = mated_board {
+            let seat = self.to_move(board);
+            self.end(BughouseResult::lost(seat, Termination::Checkmate));
+        }
+        // Alone on a board, a side without a move has nobody to wait on
+        if self.result.is_none()
+            && self.boards.len() == 1
+            && self.boards[0].mate_status() == MateStatus::MustWait
+        {
+            self.end(BughouseResult::stalemate());
+        }
+        self.history.push(outcome.clone());
+        self.undos.push(undo);
+        Ok(outcome)
+    }
+
+    /// Every move played through `make_move`, in order, across all boards.
+    #[inline]
+    pub fn get_history(&self) -> &[MoveOutcome<B>] {
+        &self.history
+    }
+
+    /// The most recent move played on `board`, if any.
+    pub fn get_last_move(&self, board: B) -> Option<&BughouseMove> {
+        self.history
+            .iter()
+            .rev()
+            .find(|outcome| outcome.get_seat().get_board() == board)
+            .map(|outcome| outcome.get_move())
+    }
+
+    /// Take back the last move played on any board, as
+    /// `BughouseGame::unmake_move` does.
+    pub fn unmake_move(&mut self) -> Option<MoveOutcome<B>> {
+        let outcome = self.history.pop()?;
+        let undo = self.undos.pop().unwrap();
+        if let Some((receiver, piece)) = outcome.get_transferred() {
+            // Anything since (e.g. the receiver dropping it) was undone first
+            self.boards[receiver.get_board().to_index()]
+                .holdings()
+                .drop(receiver.get_color(), piece)
+                .expect("transferred piece is still held");
+        }
+        let board = outcome.get_seat().get_board();
+        // Everything since was undone first, so a dropped piece has room
+        self.boards[board.to_index()]
+            .unmake_move(undo.board)
+            .expect("dropped piece fits back in hand");
+        self.result = undo.result;
+        Some(outcome)
+    }
+
+    /// Serialize every board as BFEN, separated by `" | "`.
+    pub fn to_bfen(&self) -> String {
+        self.to_bfen_with(HoldingsFormat::Slash, CastlingNotation::XFen)
+    }
+
+    /// Like `to_bfen`, with the holdings and castling rights in the given
+    /// formats.
+    pub fn to_bfen_with(
+        &self,
+        format: HoldingsFormat,
+        castling: CastlingNotation,
+    ) -> String {
+        let boards: Vec<String> = self
+            .boards
+            .iter()
+            .map(|board| board.to_bfen_with(format, castling))
+            .collect();
+        boards.join(" | ")
+    }
+}
+
+impl FromStr for MultiBughouseGame {
+    type Err = Error;
+
+    /// Leniently parse the boards (see `MultiBughouseGame::from_bfen`).
+    fn from_str(input_str: &str) -> Result<Self, Self::Err> {
+        MultiBughouseGame::from_bfen(input_str, BfenMode::Lenient)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bughouse_move::get_mv;
+    use crate::game_result::Termination;
+    use crate::Team;
+    use chess::{Color, ALL_COLORS};
+
+    fn board(idx: usize) -> MultiBoardID {
+        MultiBoardID::new(idx).unwrap()
+    }
+
+    #[test]
+    fn captures_follow_the_routing() {
+        let mut game = MultiBughouseGame::new(3).unwrap();
+        for mv in ["e2e4", "d7d5", "e4d5", "d8d5"].iter() {
+            game.make_move(board(1), &get_mv(mv)).unwrap();
+        }
+        // White on B is on Team Two, who are Black on C
+        let holdings = game.get_board(board(2)).unwrap().get_holdings();
+        assert_eq!(holdings.count(Color::Black, Piece::Pawn), 1);
+        // Black on B is on Team One, who are White on C
+        assert_eq!(holdings.count(Color::White, Piece::Pawn), 1);
+        let last = game.get_history().last().unwrap();
+        let white_c = Seat::new(board(2), Color::White);
+        assert_eq!(last.get_transferred(), Some((white_c, Piece::Pawn)));
+
+        let black_c = Seat::new(board(2), Color::Black);
+        game.unmake_move();
+        game.unmake_move();
+        let holdings = game.get_board(board(2)).unwrap().get_holdings();
+        assert_eq!(holdings.count(Color::White, Piece::Pawn), 0);
+        assert_eq!(game.get_last_move(board(1)), Some(&get_mv("d7d5")));
+
+        // Six players, everyone passing to White on A
+        let white_a = Seat::new(MultiBoardID::A, Color::White);
+        let routing = (0..6)
+            .map(|idx| Seat::new(board(idx / 2), ALL_COLORS[idx % 2]))
+            .try_fold(CaptureRouting::round_robin(3), |routing, seat| {
+                routing.with_route(seat, white_a)
+            })
+            .unwrap();
+        let boards = vec![BughouseBoard::default(); 3];
+        let mut game =
+            MultiBughouseGame::with_routing(boards, routing).unwrap();
+        for mv in ["e2e4", "d7d5", "e4d5"].iter() {
+            game.make_move(board(2), &get_mv(mv)).unwrap();
+        }
+        let seat = game.seat_to_move(board(2)).unwrap();
+        assert_eq!(seat, black_c);
+        assert!(game.make_seat_move(white_c, &get_mv("d8d5")).is_err());
+        game.make_seat_move(seat, &get_mv("d8d5")).unwrap();
+        let holdings = game.get_board(MultiBoardID::A).unwrap().get_holdings();
+        assert_eq!(holdings.count(Color::White, Piece::Pawn), 2);
+    }
+
+    #[test]
+    fn mates_end_the_game() {
+        let mated = "6rk/5Npp/8/8/8/8/8/6K1/ b - - 1 1";
+        let start = BughouseBoard::default().to_bfen();
+        let bfen = format!("{} | {} | {} | {}", start, start, start, start);
+        let mut game = MultiBughouseGame::from_str(&bfen).unwrap();
+        assert_eq!(game.num_boards(), 4);
+        assert_eq!(game.to_bfen(), bfen);
+
+        // Smothered, so no drop can block it
+        let before_mate = "6rk/6pp/8/6N1/8/8/8/6K1/ w - - 0 1";
+        let bfen = format!("{} | {} | {}", start, start, before_mate);
+        let mut three = MultiBughouseGame::from_str(&bfen).unwrap();
+        three.make_move(board(2), &get_mv("g5f7")).unwrap();
+        let result = three.get_result().unwrap();
+        assert_eq!(result.get_reason(), Termination::Checkmate);
+        // White on C is on Team One
+        assert_eq!(result.get_winner(), Some(Team::One));
+        assert_eq!(three.get_board(board(2)).unwrap().to_bfen(), mated);
+        assert!(three.make_move(MultiBoardID::A, &get_mv("e2e4")).is_err());
+
+        game.resign(Seat::new(board(3), Color::White)).unwrap();
+        assert_eq!(game.get_result().unwrap().get_winner(), Some(Team::One));
+    }
+
+    #[test]
+    fn bad_setups() {
+        let bfen = "4k3/8/8/8/8/8/8/4K3/ w - - 0 1";
+        let short = "4k3/8 w - - 0 1";
+        let offset_in = |input: &str| match BughouseBoard::from_str(input) {
+            Err(Error::BoardParseError { offset, .. }) => offset,
+            other => panic!("{:?}", other),
+        };
+        // Located within the third board
+        let bad = format!("{} | {} | {}", bfen, bfen, short);
+        match MultiBughouseGame::from_str(&bad) {
+            Err(Error::BoardParseError { offset, .. }) => {
+                assert_eq!(offset, 2 * (bfen.len() + 3) + offset_in(short))
+            }
+            other => panic!("{:?}", other),
+        }
+        assert!(MultiBughouseGame::new(0).is_err());
+        assert!(MultiBughouseGame::new(MAX_BOARDS + 1).is_err());
+        let boards = vec![BughouseBoard::default(); 2];
+        let routing = CaptureRouting::round_robin(3);
+        assert!(
+            MultiBughouseGame::with_routing(boards.clone(), routing).is_err()
+        );
+        let white_a = Seat::new(MultiBoardID::A, Color::White);
+        let white_c = Seat::new(board(2), Color::White);
+        let routing = CaptureRouting::round_robin(2);
+        assert!(routing.clone().with_route(white_a, white_c).is_err());
+        assert!(routing.with_route(white_c, white_a).is_err());
+    }
+
+    #[test]
+    fn unknown_boards() {
+        let mut game = MultiBughouseGame::new(3).unwrap();
+        let white_d = Seat::new(board(3), Color::White);
+        assert!(game.get_board(board(3)).is_none());
+        assert_eq!(game.seat_to_move(board(3)), None);
+        match game.make_move(board(3), &get_mv("e2e4")) {
+            Err(Error::UnknownBoard(id)) => assert_eq!(id, board(3)),
+            other => panic!("{:?}", other),
+        }
+        assert!(game.make_seat_move(white_d, &get_mv("e2e4")).is_err());
+        assert!(game.get_history().is_empty());
+    }
+}
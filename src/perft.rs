@@ -0,0 +1,160 @@
+use crate::bughouse_board::BughouseBoard;
+use crate::bughouse_move::BughouseMove;
+use crate::bughouse_movegen::BughouseMoveGen;
+use crate::error::Error;
+
+/// Count the leaf positions reachable from `board` in exactly `depth` plies,
+/// including drops from the board's current `Holdings`.
+///
+/// Only one board is considered, so captured pieces are never passed to a
+/// partner and the holdings only shrink as pieces are dropped.
+///
+/// ```
+/// use bughouse::{perft, BughouseBoard};
+///
+/// assert_eq!(perft(&BughouseBoard::default(), 2), 400);
+/// ```
+pub fn perft(board: &BughouseBoard, depth: usize) -> u64 {
+    perft_in_place(&mut board.clone(), depth)
+}
+
+// Makes and unmakes each move, leaving `board` as it was
+fn perft_in_place(board: &mut BughouseBoard, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = BughouseMoveGen::new_legal(board);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves
+        .map(|mv| {
+            let undo = board.make_move(&mv).unwrap_or_else(|e| fail(&mv, e));
+            let count = perft_in_place(board, depth - 1);
+            board.unmake_move(undo).unwrap_or_else(|e| fail(&mv, e));
+            count
+        })
+        .sum()
+}
+
+/// Like `perft`, but break the count down by each legal first move.
+pub fn perft_divide(
+    board: &BughouseBoard,
+    depth: usize,
+) -> Vec<(BughouseMove, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    BughouseMoveGen::new_legal(board)
+        .map(|mv| (mv, perft(&make_move_new(board, &mv), depth - 1)))
+        .collect()
+}
+
+fn make_move_new(board: &BughouseBoard, mv: &BughouseMove) -> BughouseBoard {
+    let mut next = board.clone();
+    next.make_move(mv).unwrap_or_else(|e| fail(mv, e));
+    next
+}
+
+fn fail(mv: &BughouseMove, e: Error) -> ! {
+    panic!("generated move {} failed: {}", mv, e)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bughouse_move::get_mv;
+    use chess::{MoveGen, ALL_PIECES, ALL_SQUARES};
+    use std::str::FromStr;
+
+    // Reference node counts. Positions without holdings match regular chess.
+    const PERFT_POSITIONS: [(&str, &[u64]); 6] = [
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ w KQkq - 0 1",
+            &[20, 400, 8902],
+        ),
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R/ w KQkq - 0 1",
+            &[48, 2039, 97862],
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/N w KQkq - 0 1",
+            &[52, 995, 35942],
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/Pp w KQkq - 0 1",
+            &[52, 2658, 87470],
+        ),
+        ("3k4/8/8/8/8/8/8/K6q/NP w - - 0 1", &[8, 187, 10657]),
+        (
+            "r2k1r2/pbppNppp/1p2p1nb/1P5N/3N4/4Pn1q/PPP1QP1P/2KR2R1/BrpBBqppN w - - 45 56",
+            &[108, 14020],
+        ),
+    ];
+
+    #[test]
+    fn reference_positions() {
+        for (bfen, counts) in &PERFT_POSITIONS {
+            let board = BughouseBoard::from_str(bfen).unwrap();
+            for (depth, expected) in counts.iter().enumerate() {
+                assert_eq!(perft(&board, depth + 1), *expected, "{}", bfen);
+            }
+        }
+    }
+
+    // Count moves by brute forcing `is_legal` over every drop
+    fn brute_force_perft(board: &BughouseBoard, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let mut moves: Vec<BughouseMove> =
+            MoveGen::new_legal(board.get_board())
+                .map(|mv| BughouseMove::from_chess_move(&mv))
+                .collect();
+        for sq in ALL_SQUARES.iter() {
+            for piece in ALL_PIECES[0..5].iter() {
+                let mv = BughouseMove::new(None, *sq, Some(*piece));
+                if board.is_legal(&mv) {
+                    moves.push(mv);
+                }
+            }
+        }
+        moves
+            .iter()
+            .map(|mv| brute_force_perft(&make_move_new(board, mv), depth - 1))
+            .sum()
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for (bfen, _) in &PERFT_POSITIONS[2..] {
+            let board = BughouseBoard::from_str(bfen).unwrap();
+            assert_eq!(
+                perft(&board, 2),
+                brute_force_perft(&board, 2),
+                "{}",
+                bfen
+            );
+        }
+    }
+
+    #[test]
+    fn divide_sums_to_perft() {
+        let board = BughouseBoard::from_str(PERFT_POSITIONS[2].0).unwrap();
+        let divided = perft_divide(&board, 2);
+        assert_eq!(divided.len(), 52);
+        let total: u64 = divided.iter().map(|(_, count)| count).sum();
+        assert_eq!(total, perft(&board, 2));
+        let (_, count) = divided
+            .iter()
+            .find(|(mv, _)| *mv == get_mv("N@e4"))
+            .unwrap();
+        assert_eq!(*count, 20);
+        // Black holds nothing, so a pawn advance leaves them their 20 moves
+        let (_, count) = divided
+            .iter()
+            .find(|(mv, _)| *mv == get_mv("e2e4"))
+            .unwrap();
+        assert_eq!(*count, 20);
+    }
+}
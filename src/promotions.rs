@@ -1,5 +1,8 @@
+use crate::error::*;
+use crate::zobrist;
 use chess::{
-    BitBoard, ChessMove, Color, File, Rank, Square, EMPTY, NUM_COLORS,
+    BitBoard, ChessMove, Color, File, Rank, Square, ALL_COLORS, EMPTY,
+    NUM_COLORS,
 };
 
 type PromoArray = [BitBoard; NUM_COLORS];
@@ -9,14 +12,31 @@ fn empty() -> PromoArray {
 
 /// A representation of tracking squares that have a piece promoted from a pawn.
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Promotions {
     promos: PromoArray,
+    // Kept up to date by `add_square` and `clear_square`
+    hash: u64,
 }
 
 impl Promotions {
     pub fn new(promos: &PromoArray) -> Self {
-        Promotions { promos: *promos }
+        let mut hash = 0;
+        for (color, bitboard) in ALL_COLORS.iter().zip(promos.iter()) {
+            for sq in *bitboard {
+                hash ^= zobrist::promo_key(*color, sq);
+            }
+        }
+        Promotions {
+            promos: *promos,
+            hash,
+        }
+    }
+
+    /// Zobrist hash of the promoted pieces' squares.
+    #[inline]
+    pub fn get_hash(&self) -> u64 {
+        self.hash
     }
 
     pub fn is_promo(&self, color: Color, sq: Square) -> bool {
@@ -24,11 +44,17 @@ impl Promotions {
     }
 
     pub fn add_square(&mut self, color: Color, sq: Square) {
-        self.promos[color.to_index()] |= BitBoard::from_square(sq);
+        if !self.is_promo(color, sq) {
+            self.promos[color.to_index()] |= BitBoard::from_square(sq);
+            self.hash ^= zobrist::promo_key(color, sq);
+        }
     }
 
     pub fn clear_square(&mut self, color: Color, sq: Square) {
-        self.promos[color.to_index()] &= !BitBoard::from_square(sq);
+        if self.is_promo(color, sq) {
+            self.promos[color.to_index()] &= !BitBoard::from_square(sq);
+            self.hash ^= zobrist::promo_key(color, sq);
+        }
     }
 
     pub fn record_move(&mut self, mover: Color, mv: ChessMove) {
@@ -45,36 +71,53 @@ impl Promotions {
         self.clear_square(!mover, mv.get_dest());
     }
 
-    pub fn from_fen(fen: &str) -> Promotions {
+    /// Find the promoted pieces (suffixed with `~`) in a FEN piece placement.
+    /// Fails if a `~` doesn't follow a piece on the board.
+    pub fn from_fen(fen: &str) -> Result<Promotions, Error> {
         let mut bit_boards = [EMPTY; 2];
+        let mut offset = 0;
         for (row, rank_idx) in fen.split('/').zip((0..8_usize).rev()) {
             let mut file_idx = 0;
-            let mut last_color = Color::White;
+            // The color and file of the piece just seen
+            let mut last_piece = None;
             for ch in row.chars() {
                 match ch {
                     '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' => {
-                        file_idx += (ch as usize) - ('0' as usize)
+                        file_idx += (ch as usize) - ('0' as usize);
+                        last_piece = None;
                     }
                     'p' | 'n' | 'b' | 'r' | 'q' | 'k' => {
-                        last_color = Color::Black;
+                        last_piece = Some((Color::Black, file_idx));
                         file_idx += 1;
                     }
                     'P' | 'N' | 'B' | 'R' | 'Q' | 'K' => {
-                        last_color = Color::White;
+                        last_piece = Some((Color::White, file_idx));
                         file_idx += 1;
                     }
-                    '~' => {
-                        let file = File::from_index(file_idx - 1);
-                        let rank = Rank::from_index(rank_idx);
-                        let sq = Square::make_square(rank, file);
-                        let bitboard = BitBoard::from_square(sq);
-                        bit_boards[last_color.to_index()] |= bitboard;
-                    }
-                    _ => {}
+                    '~' => match last_piece.take() {
+                        Some((color, file_idx)) if file_idx < 8 => {
+                            let file = File::from_index(file_idx);
+                            let rank = Rank::from_index(rank_idx);
+                            let sq = Square::make_square(rank, file);
+                            bit_boards[color.to_index()] |=
+                                BitBoard::from_square(sq);
+                        }
+                        _ => {
+                            return Err(Error::BoardParseError {
+                                input: fen.to_string(),
+                                field: BfenField::Placement,
+                                offset,
+                            })
+                        }
+                    },
+                    _ => last_piece = None,
                 }
+                offset += 1;
             }
+            // The slash
+            offset += 1;
         }
-        Promotions::new(&bit_boards)
+        Ok(Promotions::new(&bit_boards))
     }
 }
 
@@ -82,7 +125,10 @@ impl Promotions {
 impl Default for Promotions {
     #[inline]
     fn default() -> Self {
-        Promotions { promos: empty() }
+        Promotions {
+            promos: empty(),
+            hash: 0,
+        }
     }
 }
 
@@ -93,6 +139,11 @@ pub struct PromotionsParseError {
 
 #[test]
 fn parse_promos() {
-    let promos = Promotions::from_fen("Q~4rk1/8/8/8/8/8/8/R3K2R");
+    let promos = Promotions::from_fen("Q~4rk1/8/8/8/8/8/8/R3K2R").unwrap();
     assert!(promos.is_promo(Color::White, Square::A8));
+    assert!(!promos.is_promo(Color::White, Square::A1));
+    assert!(matches!(
+        Promotions::from_fen("4rk1/~8/8/8/8/8/8/R3K2R"),
+        Err(Error::BoardParseError { offset: 5, .. })
+    ));
 }
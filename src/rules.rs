@@ -0,0 +1,120 @@
+use chess::{get_rank, BitBoard, Color, Rank, EMPTY};
+
+/// The bughouse rules that vary between clubs and sites.  The default is the
+/// common rule set: pawns may be dropped on the 2nd through 7th ranks, drops
+/// may give checkmate, and a promoted piece reverts to a pawn when captured.
+///
+/// ```
+/// use bughouse::Rules;
+/// use chess::Rank;
+///
+/// // Chessgi: pawns may also be dropped on their own 1st rank
+/// let chessgi = Rules::new().with_pawn_drop_ranks(&[
+///     Rank::First,
+///     Rank::Second,
+///     Rank::Third,
+///     Rank::Fourth,
+///     Rank::Fifth,
+///     Rank::Sixth,
+///     Rank::Seventh,
+/// ]);
+/// assert_ne!(chessgi, Rules::default());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct Rules {
+    // Bit i set if pawns may be dropped on relative rank i
+    pawn_drop_ranks: u8,
+    drop_mates: bool,
+    promotions_revert: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::new()
+    }
+}
+
+impl Rules {
+    pub fn new() -> Self {
+        Rules {
+            pawn_drop_ranks: 0b0111_1110,
+            drop_mates: true,
+            promotions_revert: true,
+        }
+    }
+
+    /// The ranks pawns may be dropped on, counted from the dropping side
+    /// (`Rank::First` is their own back rank).  Pawns can never be dropped on
+    /// the 8th rank, where they couldn't move.
+    pub fn with_pawn_drop_ranks(mut self, ranks: &[Rank]) -> Self {
+        self.pawn_drop_ranks = ranks
+            .iter()
+            .filter(|rank| **rank != Rank::Eighth)
+            .fold(0, |bits, rank| bits | 1 << rank.to_index());
+        self
+    }
+
+    /// Whether a drop may give checkmate.
+    pub fn with_drop_mates(mut self, allowed: bool) -> Self {
+        self.drop_mates = allowed;
+        self
+    }
+
+    /// Whether a captured promoted piece reverts to a pawn (`true`) or is
+    /// passed on as the piece it promoted to.
+    pub fn with_promotions_revert(mut self, revert: bool) -> Self {
+        self.promotions_revert = revert;
+        self
+    }
+
+    /// The squares `color` may drop a pawn on (if empty).
+    pub fn get_pawn_drop_squares(&self, color: Color) -> BitBoard {
+        (0..8)
+            .filter(|idx| self.pawn_drop_ranks & 1 << idx != 0)
+            .map(|idx| match color {
+                Color::White => Rank::from_index(idx),
+                Color::Black => Rank::from_index(7 - idx),
+            })
+            .fold(EMPTY, |squares, rank| squares | get_rank(rank))
+    }
+
+    #[inline]
+    pub fn allows_drop_mates(&self) -> bool {
+        self.drop_mates
+    }
+
+    #[inline]
+    pub fn promotions_revert(&self) -> bool {
+        self.promotions_revert
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pawn_drop_squares() {
+        let rules = Rules::default();
+        let back_ranks = get_rank(Rank::First) | get_rank(Rank::Eighth);
+        assert_eq!(rules.get_pawn_drop_squares(Color::White), !back_ranks);
+        assert_eq!(rules.get_pawn_drop_squares(Color::Black), !back_ranks);
+
+        let rules = rules.with_pawn_drop_ranks(&[
+            Rank::First,
+            Rank::Second,
+            Rank::Third,
+            Rank::Eighth,
+        ]);
+        assert_eq!(
+            rules.get_pawn_drop_squares(Color::White),
+            get_rank(Rank::First) | get_rank(Rank::Second) | get_rank(Rank::Third)
+        );
+        assert_eq!(
+            rules.get_pawn_drop_squares(Color::Black),
+            get_rank(Rank::Eighth)
+                | get_rank(Rank::Seventh)
+                | get_rank(Rank::Sixth)
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use crate::bughouse_board::BughouseBoard;
+use crate::bughouse_game::{BoardID, BughouseGame, BOARD_IDS};
+use crate::bughouse_move::BughouseMove;
+use crate::holdings::NUM_HELD_PIECE_TYPES;
+use crate::terminal::glyph;
+use chess::{Color, Square, ALL_PIECES, ALL_SQUARES};
+use std::fmt::Write;
+
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+const TRAY: &str = "#d9d9d9";
+const HIGHLIGHT: &str = "#9bc700";
+const ARROW: &str = "#15781b";
+const PROMO_MARK: &str = "#c33";
+
+// Board plus a holdings tray above and below, in squares
+const BOARD_HEIGHT: u32 = 10;
+
+/// Renders positions as standalone SVG documents: each board with its
+/// holdings trays (held pieces and their counts), promoted pieces marked with
+/// a `~`, an arrow for the last move and any extra highlighted squares.
+///
+/// ```
+/// use bughouse::{BoardID, BughouseGame, SvgRenderer, Square};
+///
+/// let game = BughouseGame::default();
+/// let svg = SvgRenderer::new()
+///     .with_highlights(BoardID::A, &[Square::E4])
+///     .render(&game);
+/// assert!(svg.starts_with("<svg"));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SvgRenderer {
+    // The color at the bottom of each board
+    orientation: [Color; 2],
+    highlights: [Vec<Square>; 2],
+    square_size: u32,
+}
+
+impl Default for SvgRenderer {
+    fn default() -> Self {
+        SvgRenderer::new()
+    }
+}
+
+impl SvgRenderer {
+    /// Board A from White's side and board B from Black's, so partners sit
+    /// next to each other, with 45px squares.
+    pub fn new() -> Self {
+        SvgRenderer {
+            orientation: [Color::White, Color::Black],
+            highlights: [vec![], vec![]],
+            square_size: 45,
+        }
+    }
+
+    /// Show `board` with `bottom`'s pieces at the bottom.
+    pub fn with_orientation(mut self, board: BoardID, bottom: Color) -> Self {
+        self.orientation[board.to_index()] = bottom;
+        self
+    }
+
+    /// Highlight `squares` on `board` (e.g. the key squares of a puzzle).
+    pub fn with_highlights(
+        mut self,
+        board: BoardID,
+        squares: &[Square],
+    ) -> Self {
+        self.highlights[board.to_index()] = squares.to_vec();
+        self
+    }
+
+    /// Size, in pixels, of a square (and of a held piece in a tray).
+    pub fn with_square_size(mut self, square_size: u32) -> Self {
+        self.square_size = square_size;
+        self
+    }
+
+    #[inline]
+    pub fn get_orientation(&self, board: BoardID) -> Color {
+        self.orientation[board.to_index()]
+    }
+
+    /// Both boards side by side, one square apart, with arrows for the last
+    /// move on each.
+    pub fn render(&self, game: &BughouseGame) -> String {
+        let s = self.square_size;
+        let width = 17 * s;
+        let height = BOARD_HEIGHT * s;
+        let mut svg = header(width, height);
+        for id in BOARD_IDS.iter() {
+            let x = id.to_index() as u32 * 9 * s;
+            let _ = writeln!(svg, r#"<g transform="translate({},0)">"#, x);
+            svg.push_str(&self.board_elements(
+                game.get_board(*id),
+                self.get_orientation(*id),
+                game.get_last_move(*id),
+                &self.highlights[id.to_index()],
+            ));
+            svg.push_str("</g>\n");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// A single board with `bottom`'s pieces at the bottom, an arrow for
+    /// `last_move` if given, and board A's highlights.
+    pub fn render_board(
+        &self,
+        board: &BughouseBoard,
+        bottom: Color,
+        last_move: Option<&BughouseMove>,
+    ) -> String {
+        let s = self.square_size;
+        let mut svg = header(8 * s, BOARD_HEIGHT * s);
+        svg.push_str(&self.board_elements(
+            board,
+            bottom,
+            last_move,
+            &self.highlights[BoardID::A.to_index()],
+        ));
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // Top left corner of `sq`, below the top tray
+    fn origin(&self, sq: Square, bottom: Color) -> (u32, u32) {
+        let (file, rank) = match bottom {
+            Color::White => {
+                (sq.get_file().to_index(), 7 - sq.get_rank().to_index())
+            }
+            Color::Black => {
+                (7 - sq.get_file().to_index(), sq.get_rank().to_index())
+            }
+        };
+        let s = self.square_size;
+        (file as u32 * s, (rank as u32 + 1) * s)
+    }
+
+    fn center(&self, sq: Square, bottom: Color) -> (f64, f64) {
+        let (x, y) = self.origin(sq, bottom);
+        let half = f64::from(self.square_size) / 2.0;
+        (f64::from(x) + half, f64::from(y) + half)
+    }
+
+    fn board_elements(
+        &self,
+        board: &BughouseBoard,
+        bottom: Color,
+        last_move: Option<&BughouseMove>,
+        highlights: &[Square],
+    ) -> String {
+        let s = self.square_size;
+        let mut svg = String::new();
+        self.write_tray(&mut svg, board, !bottom, 0);
+        for sq in ALL_SQUARES.iter() {
+            let (x, y) = self.origin(*sq, bottom);
+            let is_light =
+                (sq.get_rank().to_index() + sq.get_file().to_index()) % 2 == 1;
+            let fill = if is_light { LIGHT_SQUARE } else { DARK_SQUARE };
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x, y, s, s, fill
+            );
+        }
+        let mut marked: Vec<Square> = highlights.to_vec();
+        if let Some(mv) = last_move {
+            marked.extend(mv.get_source());
+            marked.push(mv.get_dest());
+        }
+        for sq in marked.iter() {
+            let (x, y) = self.origin(*sq, bottom);
+            let _ = writeln!(
+                svg,
+                r#"<rect class="highlight" x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.5"/>"#,
+                x, y, s, s, HIGHLIGHT
+            );
+        }
+        let chess_board = board.get_board();
+        for sq in chess_board.combined().into_iter() {
+            let (piece, color) =
+                match (chess_board.piece_on(sq), chess_board.color_on(sq)) {
+                    (Some(piece), Some(color)) => (piece, color),
+                    _ => continue,
+                };
+            let (cx, cy) = self.center(sq, bottom);
+            self.write_glyph(&mut svg, glyph(piece, color), cx, cy);
+            if board.get_promos().is_promo(color, sq) {
+                let (x, y) = self.origin(sq, bottom);
+                let _ = writeln!(
+                    svg,
+                    r#"<text class="promoted" x="{}" y="{}" font-size="{}" fill="{}" text-anchor="end" dominant-baseline="hanging">~</text>"#,
+                    x + s - 2,
+                    y + 1,
+                    s / 3,
+                    PROMO_MARK
+                );
+            }
+        }
+        if let Some(mv) = last_move {
+            if let Some(source) = mv.get_source() {
+                self.write_arrow(&mut svg, source, mv.get_dest(), bottom);
+            }
+        }
+        self.write_tray(&mut svg, board, bottom, (BOARD_HEIGHT - 1) * s);
+        svg
+    }
+
+    fn write_glyph(&self, svg: &mut String, glyph: char, cx: f64, cy: f64) {
+        let _ = writeln!(
+            svg,
+            r#"<text class="piece" x="{}" y="{}" font-size="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+            cx,
+            cy,
+            self.square_size * 4 / 5,
+            glyph
+        );
+    }
+
+    // `color`'s held pieces, left to right, in a tray at `y`
+    fn write_tray(
+        &self,
+        svg: &mut String,
+        board: &BughouseBoard,
+        color: Color,
+        y: u32,
+    ) {
+        let s = self.square_size;
+        let _ = writeln!(
+            svg,
+            r#"<rect class="tray" x="0" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            y,
+            8 * s,
+            s,
+            TRAY
+        );
+        let half = f64::from(s) / 2.0;
+        let held =
+            ALL_PIECES[..NUM_HELD_PIECE_TYPES]
+                .iter()
+                .filter_map(|piece| {
+                    match board.get_holdings().count(color, *piece) {
+                        0 => None,
+                        n => Some((*piece, n)),
+                    }
+                });
+        for (i, (piece, count)) in held.enumerate() {
+            let x = i as u32 * s;
+            let cx = f64::from(x) + half;
+            self.write_glyph(svg, glyph(piece, color), cx, f64::from(y) + half);
+            if count > 1 {
+                let _ = writeln!(
+                    svg,
+                    r#"<text class="count" x="{}" y="{}" font-size="{}" text-anchor="end">{}</text>"#,
+                    x + s - 2,
+                    y + s - 2,
+                    s / 3,
+                    count
+                );
+            }
+        }
+    }
+
+    fn write_arrow(
+        &self,
+        svg: &mut String,
+        src: Square,
+        dest: Square,
+        bottom: Color,
+    ) {
+        let (x1, y1) = self.center(src, bottom);
+        let (x2, y2) = self.center(dest, bottom);
+        let _ = writeln!(
+            svg,
+            r#"<line class="arrow" x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-opacity="0.8" marker-end="url(#arrowhead)"/>"#,
+            x1,
+            y1,
+            x2,
+            y2,
+            ARROW,
+            f64::from(self.square_size) / 6.0
+        );
+    }
+}
+
+fn header(width: u32, height: u32) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<defs><marker id="arrowhead" markerWidth="3" markerHeight="3" refX="1.5" refY="1.5" orient="auto"><path d="M0,0 L3,1.5 L0,3 z" fill="{}"/></marker></defs>"#,
+        ARROW
+    );
+    svg
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bughouse_move::get_mv;
+    use std::str::FromStr;
+
+    #[test]
+    fn board_svg() {
+        let bfen = "4k3/8/8/8/8/8/8/Q~3K3/NNbp b - - 0 1";
+        let board = BughouseBoard::from_str(bfen).unwrap();
+        let renderer = SvgRenderer::new()
+            .with_square_size(40)
+            .with_highlights(BoardID::A, &[Square::E8, Square::E1]);
+        let svg = renderer.render_board(&board, Color::White, None);
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="320" height="400""#
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect x=").count(), 64);
+        assert_eq!(svg.matches(r#"class="tray""#).count(), 2);
+        assert_eq!(svg.matches(r#"class="highlight""#).count(), 2);
+        assert!(!svg.contains(r#"class="arrow""#));
+        // Three on the board, and three kinds of held piece
+        assert_eq!(svg.matches(r#"class="piece""#).count(), 6);
+        assert!(svg.contains(r#"y="60" font-size="32" text-anchor="middle" dominant-baseline="central">♚</text>"#));
+        assert!(svg.contains(r#"x="20" y="340" font-size="32" text-anchor="middle" dominant-baseline="central">♕</text>"#));
+        // Two held white knights, bottom left
+        assert!(svg.contains(r#"x="20" y="380" font-size="32" text-anchor="middle" dominant-baseline="central">♘</text>"#));
+        assert!(svg.contains(r#"font-size="13" text-anchor="end">2</text>"#));
+        assert_eq!(svg.matches(r#"class="count""#).count(), 1);
+        assert_eq!(svg.matches(r#"class="promoted""#).count(), 1);
+        assert!(svg.contains(r#"<text class="promoted" x="38" y="321""#));
+    }
+
+    #[test]
+    fn game_svg() {
+        let mut game = BughouseGame::default();
+        game.make_move(BoardID::A, &get_mv("e2e4")).unwrap();
+        game.make_move(BoardID::B, &get_mv("d2d4")).unwrap();
+        let svg = SvgRenderer::new().render(&game);
+        assert!(svg.contains(r#"width="765" height="450""#));
+        assert_eq!(svg.matches("<g transform=").count(), 2);
+        assert!(svg.contains(r#"<g transform="translate(405,0)">"#));
+        assert_eq!(svg.matches("<rect x=").count(), 128);
+        assert_eq!(svg.matches(r#"class="piece""#).count(), 64);
+        assert_eq!(svg.matches(r#"class="highlight""#).count(), 4);
+        // e2-e4 on board A, from White's side
+        assert!(svg.contains(r#"x1="202.5" y1="337.5" x2="202.5" y2="247.5""#));
+        // d2-d4 on board B, from Black's side
+        assert!(svg.contains(r#"x1="202.5" y1="112.5" x2="202.5" y2="202.5""#));
+
+        let svg = SvgRenderer::new()
+            .with_orientation(BoardID::B, Color::White)
+            .render(&game);
+        assert!(svg.contains(r#"x1="157.5" y1="337.5" x2="157.5" y2="247.5""#));
+    }
+
+    #[test]
+    fn drops_are_highlighted_without_arrows() {
+        let bfen =
+            "4k3/8/8/8/8/8/8/4K3/N w - - 0 1 | 4k3/8/8/8/8/8/8/4K3/ w - - 0 1";
+        let mut game = BughouseGame::from_str(bfen).unwrap();
+        game.make_move(BoardID::A, &get_mv("N@c3")).unwrap();
+        let svg = SvgRenderer::new().render(&game);
+        assert_eq!(svg.matches(r#"class="highlight""#).count(), 1);
+        assert!(!svg.contains(r#"class="arrow""#));
+    }
+}
@@ -0,0 +1,275 @@
+use crate::bughouse_board::BughouseBoard;
+use crate::bughouse_game::{BoardID, BughouseGame, BOARD_IDS};
+use crate::bughouse_move::BughouseMove;
+use crate::holdings::NUM_HELD_PIECE_TYPES;
+use chess::{Color, Piece, Square, ALL_FILES, ALL_PIECES, ALL_RANKS};
+
+const RESET: &str = "\x1b[0m";
+const LIGHT_SQUARE: &str = "\x1b[48;5;180m";
+const DARK_SQUARE: &str = "\x1b[48;5;137m";
+const LAST_MOVE: &str = "\x1b[48;5;143m";
+const WHITE_PIECE: &str = "\x1b[1;97m";
+const BLACK_PIECE: &str = "\x1b[1;30m";
+
+// Space between boards rendered side by side
+const GUTTER: usize = 4;
+
+pub(crate) fn glyph(piece: Piece, color: Color) -> char {
+    let glyphs = match color {
+        Color::White => ['♙', '♘', '♗', '♖', '♕', '♔'],
+        Color::Black => ['♟', '♞', '♝', '♜', '♛', '♚'],
+    };
+    glyphs[piece.to_index()]
+}
+
+// Width in terminal columns, skipping ANSI escape sequences
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for ch in line.chars() {
+        match ch {
+            '\x1b' => in_escape = true,
+            'm' if in_escape => in_escape = false,
+            _ if in_escape => {}
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+/// Renders games for a terminal with Unicode chess glyphs and (optionally)
+/// ANSI colors.  The last move on each board is highlighted, promoted pieces
+/// are suffixed with `~`, and holdings are shown as glyph counts.
+///
+/// ```
+/// use bughouse::{BoardID, BughouseGame, TerminalRenderer};
+/// use chess::Color;
+///
+/// let renderer = TerminalRenderer::new()
+///     .with_colors(false)
+///     .with_orientation(BoardID::B, Color::White);
+/// let rendered = renderer.render(&BughouseGame::default());
+/// assert!(rendered.starts_with("   Board A"));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TerminalRenderer {
+    // The color at the bottom of each board
+    orientation: [Color; 2],
+    colors: bool,
+}
+
+impl Default for TerminalRenderer {
+    fn default() -> Self {
+        TerminalRenderer::new()
+    }
+}
+
+impl TerminalRenderer {
+    /// Board A from White's side and board B from Black's, so partners sit
+    /// next to each other, with colors.
+    pub fn new() -> Self {
+        TerminalRenderer {
+            orientation: [Color::White, Color::Black],
+            colors: true,
+        }
+    }
+
+    /// Show `board` with `bottom`'s pieces at the bottom.
+    pub fn with_orientation(mut self, board: BoardID, bottom: Color) -> Self {
+        self.orientation[board.to_index()] = bottom;
+        self
+    }
+
+    /// Turn ANSI colors on or off (e.g. when not writing to a terminal).
+    pub fn with_colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    #[inline]
+    pub fn get_orientation(&self, board: BoardID) -> Color {
+        self.orientation[board.to_index()]
+    }
+
+    /// Both boards side by side, highlighting the last move on each.
+    pub fn render(&self, game: &BughouseGame) -> String {
+        let mut boards = BOARD_IDS.iter().map(|id| {
+            let last_move = game.get_last_move(*id);
+            let bottom = self.get_orientation(*id);
+            self.board_lines(game.get_board(*id), bottom, last_move)
+        });
+        let a = boards.next().unwrap();
+        let b = boards.next().unwrap();
+        let width = a.iter().map(|line| visible_width(line)).max().unwrap();
+        let mut lines = vec![format!(
+            "   {:w$}Board B",
+            "Board A",
+            w = width - 3 + GUTTER + 3
+        )];
+        for (a, b) in a.iter().zip(b.iter()) {
+            let padding = width - visible_width(a) + GUTTER;
+            lines.push(format!("{}{}{}", a, " ".repeat(padding), b));
+        }
+        lines.join("\n")
+    }
+
+    /// A single board, with `bottom`'s pieces at the bottom, highlighting
+    /// `last_move` if given.
+    pub fn render_board(
+        &self,
+        board: &BughouseBoard,
+        bottom: Color,
+        last_move: Option<&BughouseMove>,
+    ) -> String {
+        self.board_lines(board, bottom, last_move).join("\n")
+    }
+
+    fn board_lines(
+        &self,
+        board: &BughouseBoard,
+        bottom: Color,
+        last_move: Option<&BughouseMove>,
+    ) -> Vec<String> {
+        let mut ranks = ALL_RANKS.to_vec();
+        let mut files = ALL_FILES.to_vec();
+        if bottom == Color::White {
+            ranks.reverse();
+        } else {
+            files.reverse();
+        }
+        let highlighted = |sq: Square| match last_move {
+            Some(mv) => mv.get_dest() == sq || mv.get_source() == Some(sq),
+            None => false,
+        };
+        let chess_board = board.get_board();
+        let mut lines = Vec::new();
+        for rank in ranks.iter() {
+            let mut line = format!("{} ", rank.to_index() + 1);
+            for file in files.iter() {
+                let sq = Square::make_square(*rank, *file);
+                let piece = chess_board.piece_on(sq);
+                let cell = match (piece, chess_board.color_on(sq)) {
+                    (Some(piece), Some(color)) => {
+                        let promo = board.get_promos().is_promo(color, sq);
+                        let glyph = glyph(piece, color);
+                        let marker = if promo { '~' } else { ' ' };
+                        if self.colors {
+                            let fg = match color {
+                                Color::White => WHITE_PIECE,
+                                Color::Black => BLACK_PIECE,
+                            };
+                            format!(" {}{}{}", fg, glyph, marker)
+                        } else {
+                            format!(" {}{}", glyph, marker)
+                        }
+                    }
+                    _ => "   ".to_string(),
+                };
+                if self.colors {
+                    let is_light = (rank.to_index() + file.to_index()) % 2 == 1;
+                    let bg = if highlighted(sq) {
+                        LAST_MOVE
+                    } else if is_light {
+                        LIGHT_SQUARE
+                    } else {
+                        DARK_SQUARE
+                    };
+                    line.push_str(&format!("{}{}{}", bg, cell, RESET));
+                } else if highlighted(sq) {
+                    // Bracket the last move's squares instead
+                    let mut chars: Vec<char> = cell.chars().collect();
+                    chars[0] = '[';
+                    if chars[2] == ' ' {
+                        chars[2] = ']';
+                    }
+                    line.extend(chars);
+                } else {
+                    line.push_str(&cell);
+                }
+            }
+            lines.push(line);
+        }
+        let file_names: String = files
+            .iter()
+            .map(|file| format!(" {} ", (b'a' + file.to_index() as u8) as char))
+            .collect();
+        lines.push(format!("  {}", file_names.trim_end()));
+
+        // Top player's holdings above, bottom player's below
+        let held = |color| {
+            let counts: Vec<String> = ALL_PIECES[..NUM_HELD_PIECE_TYPES]
+                .iter()
+                .filter_map(|piece| {
+                    match board.get_holdings().count(color, *piece) {
+                        0 => None,
+                        1 => Some(glyph(*piece, color).to_string()),
+                        n => Some(format!("{}×{}", glyph(*piece, color), n)),
+                    }
+                })
+                .collect();
+            format!("  {:?}: {}", color, counts.join(" "))
+        };
+        lines.insert(0, held(!bottom));
+        lines.push(held(bottom));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bughouse_move::get_mv;
+    use std::str::FromStr;
+
+    fn trimmed(rendered: &str) -> Vec<&str> {
+        rendered.lines().map(|line| line.trim_end()).collect()
+    }
+
+    #[test]
+    fn plain_rendering() {
+        let bfen = "4k3/8/8/8/8/8/8/Q~3K3/NNbp b - - 0 1";
+        let board = BughouseBoard::from_str(bfen).unwrap();
+        let renderer = TerminalRenderer::new().with_colors(false);
+        let rendered = renderer.render_board(&board, Color::White, None);
+        let lines = trimmed(&rendered);
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "  Black: ♟ ♝");
+        assert_eq!(lines[1], "8              ♚");
+        assert_eq!(lines[8], "1  ♕~          ♔");
+        assert_eq!(lines[9], "   a  b  c  d  e  f  g  h");
+        assert_eq!(lines[10], "  White: ♘×2");
+
+        let flipped = renderer.render_board(&board, Color::Black, None);
+        let lines = trimmed(&flipped);
+        assert_eq!(lines[0], "  White: ♘×2");
+        assert_eq!(lines[1], "1           ♔           ♕~");
+        assert_eq!(lines[8], "8           ♚");
+        assert_eq!(lines[9], "   h  g  f  e  d  c  b  a");
+        assert_eq!(lines[10], "  Black: ♟ ♝");
+    }
+
+    #[test]
+    fn highlights_last_move() {
+        let mut game = BughouseGame::default();
+        game.make_move(BoardID::A, &get_mv("e2e4")).unwrap();
+        let renderer = TerminalRenderer::new().with_colors(false);
+        let rendered = renderer.render(&game);
+        let lines = trimmed(&rendered);
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[0], format!("   {:30}Board B", "Board A"));
+        assert!(lines[6].starts_with("4             [♙]             5"));
+        assert!(lines[8].starts_with("2  ♙  ♙  ♙  ♙ [ ] ♙  ♙  ♙     7"));
+        // Nothing to highlight on board B, which is seen from Black's side
+        assert!(lines[8].ends_with("7  ♟  ♟  ♟  ♟  ♟  ♟  ♟  ♟"));
+        assert!(lines[10].ends_with("h  g  f  e  d  c  b  a"));
+
+        let a_flipped = renderer.with_orientation(BoardID::A, Color::Black);
+        let rendered = a_flipped.render(&game);
+        let lines = trimmed(&rendered);
+        assert!(lines[3].starts_with("2  ♙  ♙  ♙ [ ] ♙  ♙  ♙  ♙"));
+
+        let colored = TerminalRenderer::new().render(&game);
+        assert_eq!(colored.matches(LAST_MOVE).count(), 2);
+        assert!(!colored.contains("[ ]"));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::holdings::NUM_HELD_PIECE_TYPES;
+use chess::{Color, File, Piece, Square, NUM_COLORS, NUM_FILES, NUM_SQUARES};
+
+// Fixed, so hashes are stable across runs (and can be stored in archives)
+const SEED: u64 = 0x6275_6768_6f75_7365; // "bughouse"
+
+// splitmix64
+pub(crate) fn next_key(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+struct Keys {
+    holdings: [[u64; NUM_HELD_PIECE_TYPES]; NUM_COLORS],
+    promos: [[u64; NUM_SQUARES]; NUM_COLORS],
+    castling: [[u64; NUM_FILES]; NUM_COLORS],
+}
+
+lazy_static! {
+    static ref KEYS: Keys = {
+        let mut state = SEED;
+        let mut keys = Keys {
+            holdings: [[0; NUM_HELD_PIECE_TYPES]; NUM_COLORS],
+            promos: [[0; NUM_SQUARES]; NUM_COLORS],
+            castling: [[0; NUM_FILES]; NUM_COLORS],
+        };
+        for color_keys in keys.holdings.iter_mut() {
+            for key in color_keys.iter_mut() {
+                *key = next_key(&mut state);
+            }
+        }
+        for color_keys in keys.promos.iter_mut() {
+            for key in color_keys.iter_mut() {
+                *key = next_key(&mut state);
+            }
+        }
+        for color_keys in keys.castling.iter_mut() {
+            for key in color_keys.iter_mut() {
+                *key = next_key(&mut state);
+            }
+        }
+        keys
+    };
+}
+
+/// Added to a `Holdings` hash once per held piece, so counts hash distinctly.
+#[inline]
+pub(crate) fn holdings_key(color: Color, piece: Piece) -> u64 {
+    KEYS.holdings[color.to_index()][piece.to_index()]
+}
+
+/// XOR'd into a `Promotions` hash for each promoted piece.
+#[inline]
+pub(crate) fn promo_key(color: Color, sq: Square) -> u64 {
+    KEYS.promos[color.to_index()][sq.to_index()]
+}
+
+/// XOR'd into a `CastlingRooks` hash for each rook that may still castle.
+#[inline]
+pub(crate) fn castling_key(color: Color, file: File) -> u64 {
+    KEYS.castling[color.to_index()][file.to_index()]
+}